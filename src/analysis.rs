@@ -0,0 +1,588 @@
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use crate::ast;
+
+//names of all properties and transport headers set, read or removed by the program
+pub fn property_names(program: &ast::Program) -> BTreeSet<&str> {
+    usages(program)
+        .into_keys()
+        .filter_map(|read| match read {
+            Read::Property(name) | Read::Axis2Property(name) | Read::Header(name) => Some(name),
+            Read::PayloadPath(_) => None,
+        })
+        .collect()
+}
+
+//property and header names which only differ in casing, e.g. X-Correlation-ID and x-correlation-id
+pub fn casing_mismatches(program: &ast::Program) -> Vec<Vec<&str>> {
+    let mut by_lowercase: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+    for name in property_names(program) {
+        by_lowercase
+            .entry(name.to_lowercase())
+            .or_default()
+            .push(name);
+    }
+
+    by_lowercase
+        .into_values()
+        .filter(|names| names.len() > 1)
+        .collect()
+}
+
+//inline endpoints of call and send mediators with the same definition, grouped by definition
+pub fn duplicate_endpoints(program: &ast::Program) -> Vec<Vec<ast::NodePath>> {
+    let mut by_definition: BTreeMap<String, Vec<ast::NodePath>> = BTreeMap::new();
+    for (path, node) in program.walk() {
+        if let Some(endpoint) = inline_endpoint(&path, node) {
+            by_definition
+                .entry(endpoint.definition())
+                .or_default()
+                .push(path);
+        }
+    }
+
+    by_definition
+        .into_values()
+        .filter(|paths| paths.len() > 1)
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Logged<'a> {
+    //log level full writes the whole payload
+    Payload,
+    //log level headers writes all transport headers
+    Headers,
+    //expression of a custom log property
+    Expression(&'a str),
+}
+
+//message content written to the log by log mediators, the first segment of each path names the
+//artifact (e.g. api[orders]) so the result can be grouped per api
+pub fn logged_content(program: &ast::Program) -> Vec<(ast::NodePath, Logged<'_>)> {
+    let mut logged = Vec::new();
+    for (path, node) in program.walk() {
+        let ast::NodeRef::Mediator(ast::Mediators::Log(log_mediator)) = node else {
+            continue;
+        };
+        match log_mediator.level.as_str() {
+            "full" => logged.push((path.clone(), Logged::Payload)),
+            "headers" => logged.push((path.clone(), Logged::Headers)),
+            _ => {}
+        }
+        for property in &log_mediator.properties {
+            if let Some(expression) = &property.expression {
+                logged.push((path.clone(), Logged::Expression(expression)));
+            }
+        }
+    }
+    logged
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Blocking {
+    //callout waits for the response of the service
+    Callout,
+    //call with blocking="true"
+    Call,
+    //dblookup and dbreport run their statements on the mediation thread
+    Database,
+}
+
+//interactions holding the mediation thread until they complete, the first segment of each path
+//names the artifact (e.g. proxy[orders]) so the result can be grouped per api or proxy
+pub fn blocking_calls(program: &ast::Program) -> Vec<(ast::NodePath, Blocking)> {
+    let mut blocking = Vec::new();
+    for (path, node) in program.walk() {
+        let ast::NodeRef::Mediator(mediator) = node else {
+            continue;
+        };
+        match mediator {
+            ast::Mediators::Callout(_) => blocking.push((path, Blocking::Callout)),
+            ast::Mediators::Call(ast::CallMediator {
+                blocking: Some(true),
+                ..
+            }) => blocking.push((path, Blocking::Call)),
+            ast::Mediators::Unparsed(element)
+                if element.name == "dblookup" || element.name == "dbreport" =>
+            {
+                blocking.push((path, Blocking::Database))
+            }
+            _ => {}
+        }
+    }
+    blocking
+}
+
+//message content read by an expression
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Read<'a> {
+    //synapse ($ctx) scope property
+    Property(&'a str),
+    Axis2Property(&'a str),
+    //transport header
+    Header(&'a str),
+    //json path of a json-eval expression
+    PayloadPath(&'a str),
+}
+
+//values read by $ctx:NAME, $axis2:NAME, $trp:NAME, get-property('[scope',] 'NAME') and
+//json-eval(PATH) expressions
+pub fn reads<'a>(expression: &'a str) -> Vec<Read<'a>> {
+    let mut reads = Vec::new();
+
+    let prefixes = [
+        ("$ctx:", Read::Property as fn(_) -> _),
+        ("$axis2:", Read::Axis2Property),
+        ("$trp:", Read::Header),
+    ];
+    for (prefix, read) in prefixes {
+        for (index, _) in expression.match_indices(prefix) {
+            let name = &expression[index + prefix.len()..];
+            let end = name
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-' || c == '.'))
+                .unwrap_or(name.len());
+            reads.push(read(&name[..end]));
+        }
+    }
+
+    for (index, _) in expression.match_indices("get-property(") {
+        let arguments = &expression[index + "get-property(".len()..];
+        let Some(end) = arguments.find(')') else {
+            continue;
+        };
+        let arguments: Vec<&str> = arguments[..end]
+            .split(',')
+            .map(|argument| argument.trim().trim_matches(|c| c == '\'' || c == '"'))
+            .collect();
+
+        match arguments.as_slice() {
+            [name] | ["default", name] => reads.push(Read::Property(name)),
+            ["axis2", name] => reads.push(Read::Axis2Property(name)),
+            ["transport", name] => reads.push(Read::Header(name)),
+            _ => {}
+        }
+    }
+
+    for (index, _) in expression.match_indices("json-eval(") {
+        let path = &expression[index + "json-eval(".len()..];
+        //the path may contain parentheses itself, e.g. in filters like $.items[?(@.id)]
+        let mut depth = 0;
+        let end = path.find(|c| {
+            match c {
+                '(' => depth += 1,
+                ')' if depth == 0 => return true,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            false
+        });
+        if let Some(end) = end {
+            reads.push(Read::PayloadPath(path[..end].trim()));
+        }
+    }
+
+    reads
+}
+
+//node tests look like function calls, e.g. child::text()
+const NODE_TESTS: &[&str] = &["text", "node", "comment", "processing-instruction"];
+
+//name and number of arguments of every function call of an expression, the fn prefix of xpath
+//functions is dropped, string literals and the json path of json-eval are not searched for calls
+pub fn function_calls(expression: &str) -> Vec<(&str, usize)> {
+    let mut calls = Vec::new();
+    let mut quote = None;
+    let mut skip_to = 0;
+
+    for (index, c) in expression.char_indices() {
+        if index < skip_to {
+            continue;
+        }
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '(') => {
+                let name = function_name(&expression[..index]);
+                let Some((length, args)) = arguments(&expression[index + 1..]) else {
+                    continue;
+                };
+                if name.is_empty() || NODE_TESTS.contains(&name) {
+                    continue;
+                }
+                if name == "json-eval" {
+                    skip_to = index + 1 + length;
+                }
+                calls.push((name.strip_prefix("fn:").unwrap_or(name), args));
+            }
+            _ => {}
+        }
+    }
+
+    calls
+}
+
+//name in front of an opening parenthesis, without the axis of a step like child::node
+fn function_name(before: &str) -> &str {
+    let name = before
+        .rsplit(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':')))
+        .next()
+        .unwrap_or_default();
+    let name = name.rsplit("::").next().unwrap_or_default();
+    name.trim_start_matches(|c: char| !(c.is_alphabetic() || c == '_'))
+}
+
+//length and number of arguments of an argument list, starting after the opening parenthesis
+fn arguments(after: &str) -> Option<(usize, usize)> {
+    let mut depth = 0;
+    let mut quote = None;
+    let mut commas = 0;
+    let mut empty = true;
+
+    for (index, c) in after.char_indices() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, ')') if depth == 0 => {
+                return Some((index, if empty { 0 } else { commas + 1 }));
+            }
+            (None, '(' | '[') => depth += 1,
+            (None, ')' | ']') => depth -= 1,
+            (None, ',') if depth == 0 => commas += 1,
+            _ => {}
+        }
+        if !c.is_whitespace() {
+            empty = false;
+        }
+    }
+
+    None
+}
+
+//mediators reading each property, header and payload path, e.g. the mediators reading
+//$ctx:ORDER_ID are found under Read::Property("ORDER_ID")
+pub fn readers(program: &ast::Program) -> BTreeMap<Read<'_>, Vec<ast::NodePath>> {
+    let mut readers: BTreeMap<Read, Vec<ast::NodePath>> = BTreeMap::new();
+    for (path, node) in program.walk() {
+        let ast::NodeRef::Mediator(mediator) = node else {
+            continue;
+        };
+        for expression in mediator.expressions() {
+            for read in reads(expression) {
+                let paths = readers.entry(read).or_default();
+                if paths.last() != Some(&path) {
+                    paths.push(path.clone());
+                }
+            }
+        }
+    }
+    readers
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Usage {
+    Set,
+    Read,
+    Remove,
+}
+
+//property or transport header set or removed by a property or header mediator, keyed like the
+//reads of expressions
+pub fn written(mediator: &ast::Mediators) -> Option<(Usage, Read<'_>)> {
+    let (name, action, scope) = match mediator {
+        ast::Mediators::Property(property) => {
+            (property.name.as_str(), property.action, property.scope)
+        }
+        ast::Mediators::Header(ast::HeaderMediator {
+            name: Some(name),
+            action,
+            scope: Some(ast::Scope::Transport),
+            ..
+        }) => (name.as_str(), *action, Some(ast::Scope::Transport)),
+        _ => return None,
+    };
+    let read = match scope {
+        None | Some(ast::Scope::Default) => Read::Property(name),
+        Some(ast::Scope::Axis2) => Read::Axis2Property(name),
+        Some(ast::Scope::Transport) => Read::Header(name),
+        _ => return None,
+    };
+    match action {
+        Some(ast::HeaderAction::Remove) => Some((Usage::Remove, read)),
+        _ => Some((Usage::Set, read)),
+    }
+}
+
+//mediators setting, reading or removing each property and transport header, e.g. the mediators
+//handling the X-Correlation-ID header are found under Read::Header("X-Correlation-ID")
+pub fn usages(program: &ast::Program) -> BTreeMap<Read<'_>, Vec<(Usage, ast::NodePath)>> {
+    let mut usages: BTreeMap<Read, Vec<(Usage, ast::NodePath)>> = BTreeMap::new();
+    for (path, node) in program.walk() {
+        let ast::NodeRef::Mediator(mediator) = node else {
+            continue;
+        };
+        if let Some((usage, written)) = written(mediator) {
+            usages
+                .entry(written)
+                .or_default()
+                .push((usage, path.clone()));
+        }
+        for expression in mediator.expressions() {
+            for read in reads(expression) {
+                if matches!(read, Read::PayloadPath(_)) {
+                    continue;
+                }
+                let uses = usages.entry(read).or_default();
+                let usage = (Usage::Read, path.clone());
+                if uses.last() != Some(&usage) {
+                    uses.push(usage);
+                }
+            }
+        }
+    }
+    usages
+}
+
+pub(crate) fn inline_endpoint<'a>(
+    path: &ast::NodePath,
+    node: ast::NodeRef<'a>,
+) -> Option<&'a ast::endpoint::Endpoint> {
+    match node {
+        //endpoint artifacts are at the root, every other endpoint is defined inline
+        ast::NodeRef::Endpoint(endpoint) if path.segments.len() > 1 => Some(endpoint),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{Blocking, Logged, Read, Usage};
+    use crate::{analysis, Parser};
+
+    #[test]
+    fn test_property_names() {
+        let input = r#"
+        <inSequence>
+            <property name="X-Correlation-ID" value="1" />
+            <log level="custom">
+                <property name="x-correlation-id" value="not a property mediator" />
+            </log>
+            <property name="foo" value="bar" />
+            <property name="x-correlation-id" value="2" />
+        </inSequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+
+        assert_eq!(
+            analysis::property_names(&program)
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec!["X-Correlation-ID", "foo", "x-correlation-id"]
+        );
+        assert_eq!(
+            analysis::casing_mismatches(&program),
+            vec![vec!["X-Correlation-ID", "x-correlation-id"]]
+        );
+
+        //transport headers and expressions are included with how each mediator handles them
+        let input = r#"
+        <inSequence>
+            <header name="X-Correlation-ID" expression="$ctx:uuid" scope="transport" />
+            <header name="Action" value="urn:getQuote" />
+            <property name="ORDER_ID" expression="$trp:x-correlation-id" />
+            <property name="ORDER_ID" action="remove" />
+            <header name="X-Correlation-ID" action="remove" scope="transport" />
+        </inSequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        let usages = analysis::usages(&program);
+        let usage = |read, index: usize| (read, format!("inSequence/mediator[{}]", index));
+        let handled: Vec<_> = usages
+            .iter()
+            .flat_map(|(read, uses)| {
+                uses.iter()
+                    .map(|(usage, path)| ((*read, *usage), path.to_string()))
+            })
+            .collect();
+        assert_eq!(
+            handled,
+            vec![
+                usage((Read::Property("ORDER_ID"), Usage::Set), 2),
+                usage((Read::Property("ORDER_ID"), Usage::Remove), 3),
+                usage((Read::Property("uuid"), Usage::Read), 0),
+                usage((Read::Header("X-Correlation-ID"), Usage::Set), 0),
+                usage((Read::Header("X-Correlation-ID"), Usage::Remove), 4),
+                usage((Read::Header("x-correlation-id"), Usage::Read), 2),
+            ]
+        );
+        assert_eq!(
+            analysis::casing_mismatches(&program),
+            vec![vec!["X-Correlation-ID", "x-correlation-id"]]
+        );
+    }
+
+    #[test]
+    fn test_logged_content() {
+        let input = r#"
+        <api context="/orders" name="orders">
+            <resource methods="POST">
+                <inSequence>
+                    <log level="full" />
+                    <filter xpath="$ctx:DEBUG">
+                        <log level="custom">
+                            <property name="email" expression="json-eval($.customer.email)" />
+                            <property name="step" value="received" />
+                        </log>
+                    </filter>
+                    <log level="headers" />
+                </inSequence>
+            </resource>
+        </api>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        let logged: Vec<(String, Logged)> = analysis::logged_content(&program)
+            .into_iter()
+            .map(|(path, logged)| (path.to_string(), logged))
+            .collect();
+
+        assert_eq!(
+            logged,
+            vec![
+                (
+                    "api[orders]/resource[0]/inSequence/mediator[0]".to_string(),
+                    Logged::Payload
+                ),
+                (
+                    "api[orders]/resource[0]/inSequence/mediator[1]/then/mediator[0]".to_string(),
+                    Logged::Expression("json-eval($.customer.email)")
+                ),
+                (
+                    "api[orders]/resource[0]/inSequence/mediator[2]".to_string(),
+                    Logged::Headers
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_blocking_calls() {
+        let input = r#"
+        <api context="/orders" name="orders">
+            <resource methods="GET" uri-template="/{id}">
+                <inSequence>
+                    <call blocking="true"><endpoint key="orders" /></call>
+                    <call><endpoint key="stock" /></call>
+                    <dblookup>
+                        <connection><pool><dsName>jdbc/orders</dsName></pool></connection>
+                        <statement><sql>select name from orders where id = ?</sql></statement>
+                    </dblookup>
+                    <respond />
+                </inSequence>
+            </resource>
+        </api>
+        <proxy name="quotes">
+            <target>
+                <inSequence>
+                    <filter xpath="$ctx:quote">
+                        <then><callout serviceURL="http://quotes:8080/quote" /></then>
+                    </filter>
+                </inSequence>
+            </target>
+        </proxy>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        let blocking: Vec<(String, Blocking)> = analysis::blocking_calls(&program)
+            .into_iter()
+            .map(|(path, blocking)| (path.to_string(), blocking))
+            .collect();
+        assert_eq!(
+            blocking,
+            vec![
+                (
+                    "api[orders]/resource[0]/inSequence/mediator[0]".to_string(),
+                    Blocking::Call
+                ),
+                (
+                    "api[orders]/resource[0]/inSequence/mediator[2]".to_string(),
+                    Blocking::Database
+                ),
+                (
+                    "proxy[quotes]/target/inSequence/mediator[0]/then/mediator[0]".to_string(),
+                    Blocking::Callout
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_readers() {
+        let input = r#"
+        <inSequence>
+            <property name="ORDER_ID" expression="json-eval($.order.id)" />
+            <switch source="get-property('transport', 'X-Tenant')">
+                <case regex="acme">
+                    <log level="custom">
+                        <property name="order" expression="$ctx:ORDER_ID" />
+                        <property name="tenant" expression="$trp:X-Tenant" />
+                    </log>
+                </case>
+            </switch>
+            <filter xpath="json-eval($.items[?(@.qty > 1)].id) = $ctx:ORDER_ID">
+                <drop/>
+            </filter>
+        </inSequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        let readers = analysis::readers(&program);
+        let paths =
+            |read| -> Vec<String> { readers[&read].iter().map(|path| path.to_string()).collect() };
+
+        assert_eq!(
+            paths(Read::Property("ORDER_ID")),
+            vec![
+                "inSequence/mediator[1]/case[0]/mediator[0]",
+                "inSequence/mediator[2]"
+            ]
+        );
+        assert_eq!(
+            paths(Read::Header("X-Tenant")),
+            vec![
+                "inSequence/mediator[1]",
+                "inSequence/mediator[1]/case[0]/mediator[0]"
+            ]
+        );
+        assert_eq!(
+            paths(Read::PayloadPath("$.items[?(@.qty > 1)].id")),
+            vec!["inSequence/mediator[2]"]
+        );
+        assert_eq!(readers.len(), 4);
+    }
+
+    #[test]
+    fn test_function_calls() {
+        assert_eq!(
+            analysis::function_calls("concat(get-property('transport', 'X-Id'), ', ', 'x(y)')"),
+            vec![("concat", 3), ("get-property", 2)]
+        );
+        assert_eq!(
+            analysis::function_calls("s11:Body/child::*[fn:position()=1]/child::text()"),
+            vec![("position", 0)]
+        );
+        assert_eq!(
+            analysis::function_calls("count(json-eval($.items[?(@.qty > 1)]))-sum(//price)"),
+            vec![("count", 1), ("json-eval", 1), ("sum", 1)]
+        );
+        assert_eq!(
+            analysis::function_calls("acme:discount($ctx:ORDER)"),
+            vec![("acme:discount", 1)]
+        );
+    }
+}
@@ -1,26 +1,579 @@
+use std::any::Any;
+use std::convert::Infallible;
+use std::fmt::Debug;
 use std::fmt::Display;
 use std::fmt::Formatter;
+use std::str::FromStr;
+
+use anyhow::bail;
+use xml::escape::{escape_str_attribute, escape_str_pcdata};
+
+pub mod endpoint;
 
 #[derive(Debug)]
 pub struct Program {
     pub ast_nodes: Vec<AstNode>,
+    pub format: SourceFormat,
+}
+
+//byte order mark and line endings of the source, reproduced when the program is serialized
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SourceFormat {
+    //utf-8 byte order mark
+    pub bom: bool,
+    pub crlf: bool,
 }
 
 #[derive(Debug)]
 pub enum AstNode {
+    Api(Api),
+    Proxy(Box<Proxy>),
+    Endpoint(endpoint::Endpoint),
     Sequence(Sequences),
+    MessageStore(MessageStore),
+    MessageProcessor(MessageProcessor),
     Mediator(Mediators),
 }
 
 #[derive(Debug)]
 pub enum Sequences {
     InSequence(InSequence),
+    OutSequence(OutSequence),
+    FaultSequence(FaultSequence),
+    NamedSequence(NamedSequence),
 }
 
 #[derive(Debug)]
 pub enum Mediators {
     Log(LogMediator),
     Property(PropertyMediator),
+    Call(CallMediator),
+    Send(SendMediator),
+    Callout(Box<CalloutMediator>),
+    Store(StoreMediator),
+    Makefault(MakefaultMediator),
+    Filter(FilterMediator),
+    Switch(SwitchMediator),
+    PayloadFactory(PayloadFactoryMediator),
+    Header(HeaderMediator),
+    Class(ClassMediator),
+    Sequence(SequenceMediator),
+    Enrich(EnrichMediator),
+    Xslt(XsltMediator),
+    Xquery(XqueryMediator),
+    Validate(ValidateMediator),
+    Script(ScriptMediator),
+    Iterate(IterateMediator),
+    Aggregate(AggregateMediator),
+    Clone(CloneMediator),
+    Cache(Box<CacheMediator>),
+    Respond,
+    Drop,
+    Loopback,
+    Extension(Box<dyn CustomMediator>),
+    //mediator listed in the catalog without a parser in this crate (e.g. the mediators of newer
+    //runtimes), kept as is so validation can report it
+    Unparsed(RawElement),
+}
+
+//user defined mediator produced by a parser registered with Parser::register_mediator,
+//Display has to write the mediator back as xml
+pub trait CustomMediator: Debug + Display {
+    fn name(&self) -> &str;
+
+    //used to downcast to the concrete mediator type
+    fn as_any(&self) -> &dyn Any;
+}
+
+//--------------------------------------------------------------------------------//
+#[derive(Debug)]
+pub struct Api {
+    pub context: String,
+    pub name: String,
+    pub xmlns: Option<String>,
+    pub trace: Option<Toggle>,
+    pub statistics: Option<Toggle>,
+    pub version: Option<String>,
+    pub version_type: Option<VersionType>,
+    pub resources: Vec<Resource>,
+    pub handlers: Vec<Handler>,
+}
+
+//java class invoked for every request of the api, e.g. for authentication
+#[derive(Debug)]
+pub struct Handler {
+    pub class: String,
+    pub properties: Vec<PropertyMediator>,
+}
+
+#[derive(Debug)]
+pub struct Resource {
+    pub methods: Vec<HttpMethod>,
+    pub uri_template: Option<String>,
+    pub url_mapping: Option<String>,
+    pub sequences: Vec<Sequences>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Put,
+    Delete,
+    Patch,
+    Head,
+    Options,
+}
+
+#[derive(Debug)]
+pub struct Proxy {
+    pub name: String,
+    pub xmlns: Option<String>,
+    pub transports: Vec<String>,
+    pub start_on_load: Option<bool>,
+    pub trace: Option<Toggle>,
+    pub statistics: Option<Toggle>,
+    pub description: Option<String>,
+    pub target: Option<ProxyTarget>,
+    pub publish_wsdl: Option<PublishWsdl>,
+    pub parameters: Vec<Parameter>,
+}
+
+//sequences are either referenced by key or defined inline
+#[derive(Debug)]
+pub struct ProxyTarget {
+    pub in_sequence: Option<String>,
+    pub out_sequence: Option<String>,
+    pub fault_sequence: Option<String>,
+    pub endpoint: Option<EndpointRef>,
+    pub sequences: Vec<Sequences>,
+}
+
+//endpoint referenced by key or defined inline
+//without endpoint the message is sent to the address of the message itself
+#[derive(Debug)]
+pub struct SendMediator {
+    pub receive: Option<String>,
+    pub build_message: Option<bool>,
+    pub endpoint: Option<EndpointRef>,
+}
+
+//blocking call to a service, the service is given by url or endpoint key
+#[derive(Debug)]
+pub struct CalloutMediator {
+    pub service_url: Option<String>,
+    pub endpoint_key: Option<String>,
+    pub action: Option<String>,
+    pub init_axis2_client_options: Option<bool>,
+    pub configuration: Option<CalloutConfiguration>,
+    pub source: Option<CalloutSource>,
+    pub target: Option<CalloutTarget>,
+    pub enable_sec: Option<EnableSec>,
+}
+
+#[derive(Debug)]
+pub struct CalloutConfiguration {
+    pub axis2xml: Option<String>,
+    pub repository: Option<String>,
+}
+
+//the request is taken from the xpath, the property with the key or the envelope (type)
+#[derive(Debug)]
+pub struct CalloutSource {
+    pub xpath: Option<String>,
+    pub key: Option<String>,
+    pub kind: Option<String>,
+    //declarations of the prefixes used by the xpath
+    pub namespaces: Vec<(String, String)>,
+}
+
+#[derive(Debug)]
+pub struct CalloutTarget {
+    pub xpath: Option<String>,
+    pub key: Option<String>,
+    pub namespaces: Vec<(String, String)>,
+}
+
+//ws-security policies, given as registry keys
+#[derive(Debug)]
+pub struct EnableSec {
+    pub policy: Option<String>,
+    pub outbound_policy: Option<String>,
+    pub inbound_policy: Option<String>,
+}
+
+//stores the message in the message store, the sequence is invoked before storing
+#[derive(Debug)]
+pub struct StoreMediator {
+    pub message_store: String,
+    pub sequence: Option<String>,
+}
+
+//replaces the message with a soap or pox fault
+#[derive(Debug)]
+pub struct MakefaultMediator {
+    //declarations of the prefixes used by the code, reason and detail
+    pub namespaces: Vec<(String, String)>,
+    pub version: Option<FaultVersion>,
+    //send the fault back to the client instead of passing it on
+    pub response: Option<bool>,
+    //qualified name, e.g. soap11Env:Client, not used for pox faults
+    pub code: Option<FaultValue>,
+    pub reason: FaultValue,
+    pub node: Option<String>,
+    pub role: Option<String>,
+    pub detail: Option<FaultDetail>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FaultVersion {
+    Soap11,
+    Soap12,
+    Pox,
+}
+
+#[derive(Debug)]
+pub enum FaultValue {
+    Value(String),
+    Expression(String),
+}
+
+//the detail is either evaluated from the expression or given inline
+#[derive(Debug)]
+pub struct FaultDetail {
+    pub expression: Option<String>,
+    pub inline: Vec<RawNode>,
+}
+
+//mediators without a then element belong to the then branch
+#[derive(Debug)]
+pub struct FilterMediator {
+    pub condition: FilterCondition,
+    pub then_mediators: Vec<Mediators>,
+    pub else_mediators: Option<Vec<Mediators>>,
+}
+
+#[derive(Debug)]
+pub enum FilterCondition {
+    Xpath(String),
+    SourceRegex { source: String, regex: String },
+}
+
+#[derive(Debug)]
+pub struct SwitchMediator {
+    pub source: String,
+    pub cases: Vec<SwitchCase>,
+    pub default: Option<Vec<Mediators>>,
+}
+
+#[derive(Debug)]
+pub struct SwitchCase {
+    pub regex: String,
+    pub mediators: Vec<Mediators>,
+}
+
+#[derive(Debug)]
+pub struct PayloadFactoryMediator {
+    pub media_type: Option<String>,
+    pub template_type: Option<String>,
+    pub format: PayloadFormat,
+    pub args: Vec<PayloadArg>,
+}
+
+#[derive(Debug)]
+pub enum PayloadFormat {
+    //registry key of the template
+    Key(String),
+    //content of the format element as written, xml elements and json or text
+    Inline(Vec<RawNode>),
+}
+
+#[derive(Debug)]
+pub enum PayloadArg {
+    Value(String),
+    Expression {
+        expression: String,
+        evaluator: Option<String>,
+        literal: Option<bool>,
+    },
+}
+
+//name is not set for inline xml headers, the element itself names the header
+#[derive(Debug)]
+pub struct HeaderMediator {
+    pub name: Option<String>,
+    pub action: Option<HeaderAction>,
+    pub scope: Option<Scope>,
+    pub value: Option<HeaderValue>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderAction {
+    Set,
+    Remove,
+}
+
+#[derive(Debug)]
+pub enum HeaderValue {
+    Value(String),
+    Expression(String),
+    Inline(RawElement),
+}
+
+//custom java mediator, properties are set on the mediator instance
+#[derive(Debug)]
+pub struct ClassMediator {
+    pub name: String,
+    pub properties: Vec<PropertyMediator>,
+}
+
+#[derive(Debug)]
+pub struct SequenceMediator {
+    pub key: String,
+}
+
+#[derive(Debug)]
+pub struct EnrichMediator {
+    pub source: EnrichSource,
+    pub target: EnrichTarget,
+}
+
+#[derive(Debug)]
+pub struct EnrichSource {
+    pub clone: Option<bool>,
+    pub kind: Option<EnrichType>,
+    pub xpath: Option<String>,
+    pub property: Option<String>,
+    pub key: Option<String>,
+    //content of an inline source, xml elements or json
+    pub inline: Vec<RawNode>,
+}
+
+#[derive(Debug)]
+pub struct EnrichTarget {
+    pub action: Option<EnrichAction>,
+    pub kind: Option<EnrichType>,
+    pub xpath: Option<String>,
+    pub property: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnrichType {
+    Custom,
+    Envelope,
+    Body,
+    Property,
+    Inline,
+    Key,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnrichAction {
+    Replace,
+    Child,
+    Sibling,
+    Remove,
+}
+
+#[derive(Debug)]
+pub struct XsltMediator {
+    pub key: String,
+    pub source: Option<String>,
+    //parameters passed to the stylesheet
+    pub properties: Vec<PropertyMediator>,
+    pub features: Vec<XsltFeature>,
+}
+
+#[derive(Debug)]
+pub struct XsltFeature {
+    pub name: String,
+    pub value: bool,
+}
+
+#[derive(Debug)]
+pub struct XqueryMediator {
+    pub key: String,
+    pub target: Option<String>,
+    pub variables: Vec<XqueryVariable>,
+}
+
+//the value is given either as value, expression or registry key
+#[derive(Debug)]
+pub struct XqueryVariable {
+    pub name: String,
+    pub kind: String,
+    pub value: Option<String>,
+    pub expression: Option<String>,
+    pub key: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ValidateMediator {
+    pub source: Option<String>,
+    //registry keys of the schemas
+    pub schemas: Vec<String>,
+    pub properties: Vec<PropertyMediator>,
+    pub on_fail: Vec<Mediators>,
+}
+
+//the script is either loaded from the registry by key or given inline as body
+#[derive(Debug)]
+pub struct ScriptMediator {
+    pub language: String,
+    pub key: Option<String>,
+    pub function: Option<String>,
+    pub body: Option<String>,
+}
+
+//splits the message and mediates every part with the target
+#[derive(Debug)]
+pub struct IterateMediator {
+    pub id: Option<String>,
+    pub expression: String,
+    pub attach_path: Option<String>,
+    pub preserve_payload: Option<bool>,
+    pub sequential: Option<bool>,
+    pub continue_parent: Option<bool>,
+    pub target: Target,
+}
+
+//hands a copy of the message to every target
+#[derive(Debug)]
+pub struct CloneMediator {
+    pub id: Option<String>,
+    pub continue_parent: Option<bool>,
+    pub sequential: Option<bool>,
+    pub targets: Vec<Target>,
+}
+
+//collects the messages split by iterate or clone
+#[derive(Debug)]
+pub struct AggregateMediator {
+    pub id: Option<String>,
+    pub correlate_on: Option<String>,
+    pub complete_condition: Option<CompleteCondition>,
+    pub on_complete: OnComplete,
+}
+
+//message counts are numbers or dynamic values like {get-property('COUNT')}
+#[derive(Debug)]
+pub struct CompleteCondition {
+    pub timeout: Option<u64>,
+    pub min_messages: Option<String>,
+    pub max_messages: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct OnComplete {
+    pub expression: String,
+    pub sequence: Option<String>,
+    pub enclosing_element_property: Option<String>,
+    pub mediators: Vec<Mediators>,
+}
+
+//the finder cache (collector false) looks up responses and the collector cache stores them,
+//timeout is in seconds and maxMessageSize in bytes
+#[derive(Debug)]
+pub struct CacheMediator {
+    pub id: Option<String>,
+    pub collector: Option<bool>,
+    pub timeout: Option<u64>,
+    pub max_message_size: Option<u64>,
+    pub on_cache_hit: Option<OnCacheHit>,
+    pub protocol: Option<CacheProtocol>,
+    pub implementation: Option<CacheImplementation>,
+}
+
+//mediators run on the cached response, the sequence is given either by key or inline
+#[derive(Debug)]
+pub struct OnCacheHit {
+    pub sequence: Option<String>,
+    pub mediators: Vec<Mediators>,
+}
+
+//header and method lists are comma separated, response codes are a regex
+#[derive(Debug)]
+pub struct CacheProtocol {
+    pub kind: String,
+    pub methods: Option<String>,
+    pub headers_to_exclude_in_hash: Option<String>,
+    pub headers_to_include_in_hash: Option<String>,
+    pub response_codes: Option<String>,
+    pub enable_cache_control: Option<bool>,
+    pub include_age_header: Option<bool>,
+    pub hash_generator: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct CacheImplementation {
+    pub kind: Option<String>,
+    pub max_size: Option<u64>,
+}
+
+//message copy handed to a sequence and/or endpoint by iterate and clone
+#[derive(Debug)]
+pub struct Target {
+    pub to: Option<String>,
+    pub soap_action: Option<String>,
+    pub sequence: Option<SequenceRef>,
+    pub endpoint: Option<EndpointRef>,
+}
+
+#[derive(Debug)]
+pub enum SequenceRef {
+    Key(String),
+    Inline(Vec<Mediators>),
+}
+
+#[derive(Debug)]
+pub enum EndpointRef {
+    Key(String),
+    Inline(Box<endpoint::Endpoint>),
+}
+
+#[derive(Debug)]
+pub struct PublishWsdl {
+    pub uri: Option<String>,
+    pub key: Option<String>,
+    pub preserve_policy: Option<bool>,
+    pub definition: Option<RawElement>,
+    pub resources: Vec<WsdlResource>,
+}
+
+#[derive(Debug)]
+pub struct WsdlResource {
+    pub location: Option<String>,
+    pub key: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Parameter {
+    pub name: String,
+    pub value: String,
+}
+
+//value of enable/disable attributes like trace and statistics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Toggle {
+    Enable,
+    Disable,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionType {
+    Url,
+    Context,
+}
+
+//message context scope of properties and headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Default,
+    Transport,
+    Axis2,
+    Axis2Client,
+    Operation,
+    Registry,
 }
 
 //--------------------------------------------------------------------------------//
@@ -29,6 +582,48 @@ pub struct InSequence {
     pub mediators: Vec<Mediators>,
 }
 
+#[derive(Debug)]
+pub struct OutSequence {
+    pub mediators: Vec<Mediators>,
+}
+
+#[derive(Debug)]
+pub struct FaultSequence {
+    pub mediators: Vec<Mediators>,
+}
+
+//reusable sequence defined as its own artifact, invoked by sequence mediators via its name
+#[derive(Debug)]
+pub struct NamedSequence {
+    pub name: String,
+    pub xmlns: Option<String>,
+    pub on_error: Option<String>,
+    pub trace: Option<Toggle>,
+    pub statistics: Option<Toggle>,
+    pub mediators: Vec<Mediators>,
+}
+
+//queue or database messages are stored in by store mediators, the store implementation is
+//given by class and configured by the parameters
+#[derive(Debug)]
+pub struct MessageStore {
+    pub name: String,
+    pub xmlns: Option<String>,
+    pub class: Option<String>,
+    pub parameters: Vec<Parameter>,
+}
+
+//processes the messages of a message store, e.g. forwards them to the target endpoint
+#[derive(Debug)]
+pub struct MessageProcessor {
+    pub name: String,
+    pub xmlns: Option<String>,
+    pub class: String,
+    pub message_store: String,
+    pub target_endpoint: Option<String>,
+    pub parameters: Vec<Parameter>,
+}
+
 #[derive(Debug)]
 pub struct LogMediator {
     pub level: String,
@@ -38,81 +633,2064 @@ pub struct LogMediator {
 #[derive(Debug)]
 pub struct PropertyMediator {
     pub name: String,
+    pub action: Option<HeaderAction>,
     pub value: String,
+    pub expression: Option<String>,
+    pub scope: Option<Scope>,
+    //value type like STRING, INTEGER or OM, the runtime defaults to STRING
+    pub kind: Option<String>,
+    //regex applied to the value, the group selects the part of the match which is set
+    pub pattern: Option<String>,
+    pub group: Option<u32>,
+    //inline xml value of OM properties
+    pub inline: Vec<RawNode>,
+}
+
+#[derive(Debug)]
+pub struct CallMediator {
+    pub blocking: Option<bool>,
+    pub endpoint: Option<EndpointRef>,
 }
 
 //--------------------------------------------------------------------------------//
-impl IntoIterator for Program {
-    type Item = AstNode;
-    type IntoIter = std::vec::IntoIter<Self::Item>;
+//element captured as is, used for content the parser does not interpret
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawElement {
+    pub prefix: Option<String>,
+    pub namespace: Option<String>,
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    //namespace declarations of the element, the prefix is empty for the default namespace
+    pub namespaces: Vec<(String, String)>,
+    pub children: Vec<RawNode>,
+}
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.ast_nodes.into_iter()
-    }
+#[derive(Debug, Clone, PartialEq)]
+pub enum RawNode {
+    Element(RawElement),
+    Text(String),
+    CData(String),
 }
 
 //--------------------------------------------------------------------------------//
-impl Display for Program {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        for ast_node in &self.ast_nodes {
-            write!(f, "{}", ast_node)?;
+//address of a node inside a program, e.g. inSequence/mediator[2]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct NodePath {
+    pub segments: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum NodeRef<'a> {
+    Api(&'a Api),
+    Proxy(&'a Proxy),
+    Endpoint(&'a endpoint::Endpoint),
+    MessageStore(&'a MessageStore),
+    MessageProcessor(&'a MessageProcessor),
+    Resource(&'a Resource),
+    Sequence(&'a Sequences),
+    Mediator(&'a Mediators),
+}
+
+//--------------------------------------------------------------------------------//
+impl Program {
+    //all nodes of the program with their path in document order
+    pub fn walk(&self) -> Vec<(NodePath, NodeRef<'_>)> {
+        let mut nodes = Vec::new();
+        //repeated unnamed root sequences are addressed as inSequence, inSequence[1], ...
+        let mut root_sequences: Vec<&str> = Vec::new();
+        for (index, ast_node) in self.ast_nodes.iter().enumerate() {
+            match ast_node {
+                AstNode::Api(api) => {
+                    let path = NodePath::default().child(format!("api[{}]", api.name));
+                    nodes.push((path.clone(), NodeRef::Api(api)));
+                    for (index, resource) in api.resources.iter().enumerate() {
+                        let path = path.child(format!("resource[{}]", index));
+                        nodes.push((path.clone(), NodeRef::Resource(resource)));
+                        for sequence in &resource.sequences {
+                            walk_sequence(&path, sequence, &mut nodes);
+                        }
+                    }
+                }
+                AstNode::Proxy(proxy) => {
+                    let path = NodePath::default().child(format!("proxy[{}]", proxy.name));
+                    nodes.push((path.clone(), NodeRef::Proxy(proxy)));
+                    if let Some(target) = &proxy.target {
+                        let path = path.child("target");
+                        for sequence in &target.sequences {
+                            walk_sequence(&path, sequence, &mut nodes);
+                        }
+                        if let Some(EndpointRef::Inline(endpoint)) = &target.endpoint {
+                            nodes.push((path.child("endpoint"), NodeRef::Endpoint(endpoint)));
+                        }
+                    }
+                }
+                AstNode::Endpoint(endpoint) => {
+                    let name = endpoint.name.as_deref().unwrap_or_default();
+                    let path = NodePath::default().child(format!("endpoint[{}]", name));
+                    nodes.push((path, NodeRef::Endpoint(endpoint)));
+                }
+                AstNode::Sequence(sequence @ Sequences::NamedSequence(_)) => {
+                    walk_sequence(&NodePath::default(), sequence, &mut nodes);
+                }
+                AstNode::Sequence(sequence) => {
+                    let name = sequence.name();
+                    let count = root_sequences.iter().filter(|seen| **seen == name).count();
+                    root_sequences.push(name);
+                    let path = match count {
+                        0 => NodePath::default().child(name),
+                        count => NodePath::default().child(format!("{}[{}]", name, count)),
+                    };
+                    nodes.push((path.clone(), NodeRef::Sequence(sequence)));
+                    walk_mediators(&path, sequence.mediators(), &mut nodes);
+                }
+                AstNode::MessageStore(message_store) => {
+                    let path =
+                        NodePath::default().child(format!("messageStore[{}]", message_store.name));
+                    nodes.push((path, NodeRef::MessageStore(message_store)));
+                }
+                AstNode::MessageProcessor(message_processor) => {
+                    let path = NodePath::default()
+                        .child(format!("messageProcessor[{}]", message_processor.name));
+                    nodes.push((path, NodeRef::MessageProcessor(message_processor)));
+                }
+                AstNode::Mediator(mediator) => {
+                    let path = NodePath::default().child(format!("mediator[{}]", index));
+                    walk_mediator(path, mediator, &mut nodes);
+                }
+            }
         }
-        Ok(())
+        nodes
     }
-}
 
-impl Display for AstNode {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            AstNode::Sequence(sequence) => write!(f, "{}", sequence),
-            AstNode::Mediator(mediator) => write!(f, "{}", mediator),
+    //all mediators of the program in document order
+    pub fn mediators(&self) -> Vec<&Mediators> {
+        self.walk()
+            .into_iter()
+            .filter_map(|(_, node)| match node {
+                NodeRef::Mediator(mediator) => Some(mediator),
+                _ => None,
+            })
+            .collect()
+    }
+
+    //call f for every mediator of the program, including mediators nested in other mediators
+    pub fn for_each_mediator_mut(&mut self, f: &mut impl FnMut(&mut Mediators)) {
+        for ast_node in &mut self.ast_nodes {
+            match ast_node {
+                AstNode::Api(api) => {
+                    for resource in &mut api.resources {
+                        for sequence in &mut resource.sequences {
+                            for_each_mediator_mut(sequence.mediators_mut(), f);
+                        }
+                    }
+                }
+                AstNode::Proxy(proxy) => {
+                    if let Some(target) = &mut proxy.target {
+                        for sequence in &mut target.sequences {
+                            for_each_mediator_mut(sequence.mediators_mut(), f);
+                        }
+                    }
+                }
+                AstNode::Endpoint(_) | AstNode::MessageStore(_) | AstNode::MessageProcessor(_) => {}
+                AstNode::Sequence(sequence) => for_each_mediator_mut(sequence.mediators_mut(), f),
+                AstNode::Mediator(mediator) => {
+                    for_each_mediator_mut(std::slice::from_mut(mediator), f)
+                }
+            }
         }
     }
-}
 
-impl Display for Sequences {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Sequences::InSequence(in_sequence) => write!(f, "{}", in_sequence),
+    //endpoints referenced or defined by proxy targets and mediators, endpoint artifacts are not
+    //included
+    pub fn for_each_endpoint_mut(&mut self, f: &mut impl FnMut(&mut Option<EndpointRef>)) {
+        for ast_node in &mut self.ast_nodes {
+            if let AstNode::Proxy(proxy) = ast_node {
+                if let Some(target) = &mut proxy.target {
+                    f(&mut target.endpoint);
+                }
+            }
         }
+        self.for_each_mediator_mut(&mut |mediator| {
+            for endpoint in mediator.endpoints_mut() {
+                f(endpoint);
+            }
+        });
+    }
+
+    pub fn find(&self, path: &NodePath) -> Option<NodeRef<'_>> {
+        self.walk()
+            .into_iter()
+            .find(|(node_path, _)| node_path == path)
+            .map(|(_, node)| node)
     }
 }
 
-impl Display for InSequence {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "<inSequence>")?;
-        for mediator in &self.mediators {
-            write!(f, "{}", mediator)?;
+fn for_each_mediator_mut(mediators: &mut [Mediators], f: &mut impl FnMut(&mut Mediators)) {
+    for mediator in mediators {
+        f(mediator);
+        for branch in mediator.branches_mut() {
+            for_each_mediator_mut(branch, f);
         }
-        write!(f, "</inSequence>")
     }
 }
 
-impl Display for Mediators {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Mediators::Log(log_mediator) => write!(f, "{}", log_mediator),
-            Mediators::Property(property_mediator) => write!(f, "{}", property_mediator),
+fn walk_sequence<'a>(
+    parent: &NodePath,
+    sequence: &'a Sequences,
+    nodes: &mut Vec<(NodePath, NodeRef<'a>)>,
+) {
+    let path = match sequence {
+        Sequences::NamedSequence(named_sequence) => {
+            parent.child(format!("sequence[{}]", named_sequence.name))
         }
-    }
+        _ => parent.child(sequence.name()),
+    };
+    nodes.push((path.clone(), NodeRef::Sequence(sequence)));
+    walk_mediators(&path, sequence.mediators(), nodes);
 }
 
-impl Display for LogMediator {
+fn walk_mediators<'a>(
+    parent: &NodePath,
+    mediators: &'a [Mediators],
+    nodes: &mut Vec<(NodePath, NodeRef<'a>)>,
+) {
+    for (index, mediator) in mediators.iter().enumerate() {
+        walk_mediator(
+            parent.child(format!("mediator[{}]", index)),
+            mediator,
+            nodes,
+        );
+    }
+}
+
+fn walk_mediator<'a>(
+    path: NodePath,
+    mediator: &'a Mediators,
+    nodes: &mut Vec<(NodePath, NodeRef<'a>)>,
+) {
+    nodes.push((path.clone(), NodeRef::Mediator(mediator)));
+    for (relative, endpoint_ref) in mediator.endpoints() {
+        if let EndpointRef::Inline(endpoint) = endpoint_ref {
+            let mut endpoint_path = path.clone();
+            endpoint_path.segments.extend(relative.segments);
+            nodes.push((endpoint_path, NodeRef::Endpoint(endpoint)));
+        }
+    }
+    for (name, branch) in mediator.branches() {
+        walk_mediators(&path.child(name), branch, nodes);
+    }
+}
+
+impl NodePath {
+    pub fn child(&self, segment: impl Into<String>) -> NodePath {
+        let mut segments = self.segments.clone();
+        segments.push(segment.into());
+        NodePath { segments }
+    }
+}
+
+impl Resource {
+    pub fn in_sequence(&self) -> Option<&InSequence> {
+        self.sequences.iter().find_map(|sequence| match sequence {
+            Sequences::InSequence(in_sequence) => Some(in_sequence),
+            _ => None,
+        })
+    }
+
+    pub fn out_sequence(&self) -> Option<&OutSequence> {
+        self.sequences.iter().find_map(|sequence| match sequence {
+            Sequences::OutSequence(out_sequence) => Some(out_sequence),
+            _ => None,
+        })
+    }
+
+    pub fn fault_sequence(&self) -> Option<&FaultSequence> {
+        self.sequences.iter().find_map(|sequence| match sequence {
+            Sequences::FaultSequence(fault_sequence) => Some(fault_sequence),
+            _ => None,
+        })
+    }
+}
+
+impl Sequences {
+    //element name of the sequence
+    pub fn name(&self) -> &'static str {
+        match self {
+            Sequences::InSequence(_) => "inSequence",
+            Sequences::OutSequence(_) => "outSequence",
+            Sequences::FaultSequence(_) => "faultSequence",
+            Sequences::NamedSequence(_) => "sequence",
+        }
+    }
+
+    pub fn mediators(&self) -> &[Mediators] {
+        match self {
+            Sequences::InSequence(in_sequence) => &in_sequence.mediators,
+            Sequences::OutSequence(out_sequence) => &out_sequence.mediators,
+            Sequences::FaultSequence(fault_sequence) => &fault_sequence.mediators,
+            Sequences::NamedSequence(named_sequence) => &named_sequence.mediators,
+        }
+    }
+
+    pub fn mediators_mut(&mut self) -> &mut Vec<Mediators> {
+        match self {
+            Sequences::InSequence(in_sequence) => &mut in_sequence.mediators,
+            Sequences::OutSequence(out_sequence) => &mut out_sequence.mediators,
+            Sequences::FaultSequence(fault_sequence) => &mut fault_sequence.mediators,
+            Sequences::NamedSequence(named_sequence) => &mut named_sequence.mediators,
+        }
+    }
+}
+
+impl Mediators {
+    //element name of the mediator
+    pub fn name(&self) -> &str {
+        match self {
+            Mediators::Log(_) => "log",
+            Mediators::Property(_) => "property",
+            Mediators::Call(_) => "call",
+            Mediators::Send(_) => "send",
+            Mediators::Callout(_) => "callout",
+            Mediators::Store(_) => "store",
+            Mediators::Makefault(_) => "makefault",
+            Mediators::Filter(_) => "filter",
+            Mediators::Switch(_) => "switch",
+            Mediators::PayloadFactory(_) => "payloadFactory",
+            Mediators::Header(_) => "header",
+            Mediators::Class(_) => "class",
+            Mediators::Sequence(_) => "sequence",
+            Mediators::Enrich(_) => "enrich",
+            Mediators::Xslt(_) => "xslt",
+            Mediators::Xquery(_) => "xquery",
+            Mediators::Validate(_) => "validate",
+            Mediators::Script(_) => "script",
+            Mediators::Iterate(_) => "iterate",
+            Mediators::Aggregate(_) => "aggregate",
+            Mediators::Clone(_) => "clone",
+            Mediators::Cache(_) => "cache",
+            Mediators::Respond => "respond",
+            Mediators::Drop => "drop",
+            Mediators::Loopback => "loopback",
+            Mediators::Extension(extension) => extension.name(),
+            Mediators::Unparsed(element) => &element.name,
+        }
+    }
+
+    //xpath and json path expressions evaluated by the mediator itself
+    pub fn expressions(&self) -> Vec<&str> {
+        match self {
+            Mediators::Property(property) => {
+                property.expression.iter().map(String::as_str).collect()
+            }
+            Mediators::Log(log_mediator) => log_mediator
+                .properties
+                .iter()
+                .filter_map(|property| property.expression.as_deref())
+                .collect(),
+            Mediators::Filter(filter_mediator) => match &filter_mediator.condition {
+                FilterCondition::Xpath(xpath) => vec![xpath.as_str()],
+                FilterCondition::SourceRegex { source, .. } => vec![source.as_str()],
+            },
+            Mediators::Switch(switch_mediator) => vec![switch_mediator.source.as_str()],
+            Mediators::Header(HeaderMediator {
+                value: Some(HeaderValue::Expression(expression)),
+                ..
+            }) => vec![expression.as_str()],
+            Mediators::Enrich(enrich_mediator) => enrich_mediator
+                .source
+                .xpath
+                .iter()
+                .chain(&enrich_mediator.target.xpath)
+                .map(String::as_str)
+                .collect(),
+            Mediators::Xslt(xslt_mediator) => xslt_mediator
+                .properties
+                .iter()
+                .filter_map(|property| property.expression.as_deref())
+                .collect(),
+            Mediators::Xquery(xquery_mediator) => xquery_mediator
+                .variables
+                .iter()
+                .filter_map(|variable| variable.expression.as_deref())
+                .collect(),
+            Mediators::Validate(validate_mediator) => validate_mediator
+                .source
+                .iter()
+                .map(String::as_str)
+                .collect(),
+            Mediators::Callout(callout_mediator) => callout_mediator
+                .source
+                .as_ref()
+                .and_then(|source| source.xpath.as_deref())
+                .into_iter()
+                .chain(
+                    callout_mediator
+                        .target
+                        .as_ref()
+                        .and_then(|target| target.xpath.as_deref()),
+                )
+                .collect(),
+            Mediators::Makefault(makefault_mediator) => [&makefault_mediator.code]
+                .into_iter()
+                .flatten()
+                .chain([&makefault_mediator.reason])
+                .filter_map(|value| match value {
+                    FaultValue::Expression(expression) => Some(expression.as_str()),
+                    FaultValue::Value(_) => None,
+                })
+                .chain(
+                    makefault_mediator
+                        .detail
+                        .as_ref()
+                        .and_then(|detail| detail.expression.as_deref()),
+                )
+                .collect(),
+            Mediators::PayloadFactory(payload_factory) => payload_factory
+                .args
+                .iter()
+                .filter_map(|arg| match arg {
+                    PayloadArg::Expression { expression, .. } => Some(expression.as_str()),
+                    PayloadArg::Value(_) => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    //mediators nested in this mediator, keyed by the element containing them
+    pub fn branches(&self) -> Vec<(String, &[Mediators])> {
+        match self {
+            Mediators::Filter(filter) => {
+                let mut branches = vec![("then".to_string(), filter.then_mediators.as_slice())];
+                if let Some(else_mediators) = &filter.else_mediators {
+                    branches.push(("else".to_string(), else_mediators.as_slice()));
+                }
+                branches
+            }
+            Mediators::Switch(switch) => {
+                let mut branches: Vec<(String, &[Mediators])> = switch
+                    .cases
+                    .iter()
+                    .enumerate()
+                    .map(|(index, case)| (format!("case[{}]", index), case.mediators.as_slice()))
+                    .collect();
+                if let Some(default) = &switch.default {
+                    branches.push(("default".to_string(), default.as_slice()));
+                }
+                branches
+            }
+            Mediators::Validate(validate) => {
+                vec![("on-fail".to_string(), validate.on_fail.as_slice())]
+            }
+            Mediators::Iterate(iterate) => match &iterate.target.sequence {
+                Some(SequenceRef::Inline(mediators)) => {
+                    vec![("target".to_string(), mediators.as_slice())]
+                }
+                _ => Vec::new(),
+            },
+            Mediators::Aggregate(aggregate) => vec![(
+                "onComplete".to_string(),
+                aggregate.on_complete.mediators.as_slice(),
+            )],
+            Mediators::Clone(clone) => clone
+                .targets
+                .iter()
+                .enumerate()
+                .filter_map(|(index, target)| match &target.sequence {
+                    Some(SequenceRef::Inline(mediators)) => {
+                        Some((format!("target[{}]", index), mediators.as_slice()))
+                    }
+                    _ => None,
+                })
+                .collect(),
+            Mediators::Cache(cache) => cache
+                .on_cache_hit
+                .iter()
+                .map(|on_cache_hit| ("onCacheHit".to_string(), on_cache_hit.mediators.as_slice()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn branches_mut(&mut self) -> Vec<&mut Vec<Mediators>> {
+        match self {
+            Mediators::Filter(filter) => {
+                let mut branches = vec![&mut filter.then_mediators];
+                if let Some(else_mediators) = &mut filter.else_mediators {
+                    branches.push(else_mediators);
+                }
+                branches
+            }
+            Mediators::Switch(switch) => {
+                let mut branches: Vec<&mut Vec<Mediators>> = switch
+                    .cases
+                    .iter_mut()
+                    .map(|case| &mut case.mediators)
+                    .collect();
+                if let Some(default) = &mut switch.default {
+                    branches.push(default);
+                }
+                branches
+            }
+            Mediators::Validate(validate) => vec![&mut validate.on_fail],
+            Mediators::Iterate(iterate) => match &mut iterate.target.sequence {
+                Some(SequenceRef::Inline(mediators)) => vec![mediators],
+                _ => Vec::new(),
+            },
+            Mediators::Aggregate(aggregate) => vec![&mut aggregate.on_complete.mediators],
+            Mediators::Clone(clone) => clone
+                .targets
+                .iter_mut()
+                .filter_map(|target| match &mut target.sequence {
+                    Some(SequenceRef::Inline(mediators)) => Some(mediators),
+                    _ => None,
+                })
+                .collect(),
+            Mediators::Cache(cache) => cache
+                .on_cache_hit
+                .iter_mut()
+                .map(|on_cache_hit| &mut on_cache_hit.mediators)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn endpoints_mut(&mut self) -> Vec<&mut Option<EndpointRef>> {
+        match self {
+            Mediators::Call(call) => vec![&mut call.endpoint],
+            Mediators::Send(send) => vec![&mut send.endpoint],
+            Mediators::Iterate(iterate) => vec![&mut iterate.target.endpoint],
+            Mediators::Clone(clone) => clone
+                .targets
+                .iter_mut()
+                .map(|target| &mut target.endpoint)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    //endpoints the mediator sends the message to, with their path relative to the mediator
+    pub fn endpoints(&self) -> Vec<(NodePath, &EndpointRef)> {
+        let endpoint = NodePath::default().child("endpoint");
+        match self {
+            Mediators::Call(CallMediator {
+                endpoint: Some(endpoint_ref),
+                ..
+            })
+            | Mediators::Send(SendMediator {
+                endpoint: Some(endpoint_ref),
+                ..
+            }) => vec![(endpoint, endpoint_ref)],
+            Mediators::Iterate(iterate) => match &iterate.target.endpoint {
+                Some(endpoint_ref) => {
+                    vec![(
+                        NodePath::default().child("target").child("endpoint"),
+                        endpoint_ref,
+                    )]
+                }
+                None => Vec::new(),
+            },
+            Mediators::Clone(clone) => clone
+                .targets
+                .iter()
+                .enumerate()
+                .filter_map(|(index, target)| {
+                    let path = NodePath::default()
+                        .child(format!("target[{}]", index))
+                        .child("endpoint");
+                    target
+                        .endpoint
+                        .as_ref()
+                        .map(|endpoint_ref| (path, endpoint_ref))
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl IntoIterator for Program {
+    type Item = AstNode;
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ast_nodes.into_iter()
+    }
+}
+
+//--------------------------------------------------------------------------------//
+impl Display for NodePath {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "<log level=\"{}\">", self.level)?;
+        write!(f, "{}", self.segments.join("/"))
+    }
+}
+
+impl FromStr for NodePath {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(NodePath {
+            segments: s
+                .split('/')
+                .filter(|segment| !segment.is_empty())
+                .map(String::from)
+                .collect(),
+        })
+    }
+}
+
+impl Display for Program {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.format.bom {
+            write!(f, "\u{feff}")?;
+        }
+        for ast_node in &self.ast_nodes {
+            //line breaks only occur in text content, which may hold both kinds of line endings
+            match self.format.crlf {
+                true => write!(
+                    f,
+                    "{}",
+                    ast_node
+                        .to_string()
+                        .replace("\r\n", "\n")
+                        .replace('\n', "\r\n")
+                )?,
+                false => write!(f, "{}", ast_node)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for AstNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AstNode::Api(api) => write!(f, "{}", api),
+            AstNode::Proxy(proxy) => write!(f, "{}", proxy),
+            AstNode::Endpoint(endpoint) => write!(f, "{}", endpoint),
+            AstNode::Sequence(sequence) => write!(f, "{}", sequence),
+            AstNode::MessageStore(message_store) => write!(f, "{}", message_store),
+            AstNode::MessageProcessor(message_processor) => write!(f, "{}", message_processor),
+            AstNode::Mediator(mediator) => write!(f, "{}", mediator),
+        }
+    }
+}
+
+impl Display for Api {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<api context=\"{}\" name=\"{}\"",
+            escape_str_attribute(&self.context),
+            escape_str_attribute(&self.name)
+        )?;
+        if let Some(xmlns) = &self.xmlns {
+            write!(f, " xmlns=\"{}\"", escape_str_attribute(xmlns))?;
+        }
+        if let Some(trace) = &self.trace {
+            write!(f, " trace=\"{}\"", trace)?;
+        }
+        if let Some(statistics) = &self.statistics {
+            write!(f, " statistics=\"{}\"", statistics)?;
+        }
+        if let Some(version) = &self.version {
+            write!(f, " version=\"{}\"", escape_str_attribute(version))?;
+        }
+        if let Some(version_type) = &self.version_type {
+            write!(f, " version-type=\"{}\"", version_type)?;
+        }
+        write!(f, ">")?;
+        for resource in &self.resources {
+            write!(f, "{}", resource)?;
+        }
+        if !self.handlers.is_empty() {
+            write!(f, "<handlers>")?;
+            for handler in &self.handlers {
+                write!(f, "{}", handler)?;
+            }
+            write!(f, "</handlers>")?;
+        }
+        write!(f, "</api>")
+    }
+}
+
+impl Display for Handler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<handler class=\"{}\"",
+            escape_str_attribute(&self.class)
+        )?;
+        if self.properties.is_empty() {
+            return write!(f, "/>");
+        }
+
+        write!(f, ">")?;
         for property in &self.properties {
             write!(f, "{}", property)?;
         }
-        write!(f, "</log>")
+        write!(f, "</handler>")
     }
 }
 
-impl Display for PropertyMediator {
+impl Display for Proxy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<proxy name=\"{}\"", escape_str_attribute(&self.name))?;
+        if let Some(xmlns) = &self.xmlns {
+            write!(f, " xmlns=\"{}\"", escape_str_attribute(xmlns))?;
+        }
+        if !self.transports.is_empty() {
+            write!(
+                f,
+                " transports=\"{}\"",
+                escape_str_attribute(&self.transports.join(" "))
+            )?;
+        }
+        if let Some(start_on_load) = &self.start_on_load {
+            write!(f, " startOnLoad=\"{}\"", start_on_load)?;
+        }
+        if let Some(trace) = &self.trace {
+            write!(f, " trace=\"{}\"", trace)?;
+        }
+        if let Some(statistics) = &self.statistics {
+            write!(f, " statistics=\"{}\"", statistics)?;
+        }
+        write!(f, ">")?;
+        if let Some(description) = &self.description {
+            write!(
+                f,
+                "<description>{}</description>",
+                escape_str_pcdata(description)
+            )?;
+        }
+        if let Some(target) = &self.target {
+            write!(f, "{}", target)?;
+        }
+        if let Some(publish_wsdl) = &self.publish_wsdl {
+            write!(f, "{}", publish_wsdl)?;
+        }
+        for parameter in &self.parameters {
+            write!(f, "{}", parameter)?;
+        }
+        write!(f, "</proxy>")
+    }
+}
+
+impl Display for ProxyTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<target")?;
+        if let Some(in_sequence) = &self.in_sequence {
+            write!(f, " inSequence=\"{}\"", escape_str_attribute(in_sequence))?;
+        }
+        if let Some(out_sequence) = &self.out_sequence {
+            write!(f, " outSequence=\"{}\"", escape_str_attribute(out_sequence))?;
+        }
+        if let Some(fault_sequence) = &self.fault_sequence {
+            write!(
+                f,
+                " faultSequence=\"{}\"",
+                escape_str_attribute(fault_sequence)
+            )?;
+        }
+        if let Some(EndpointRef::Key(key)) = &self.endpoint {
+            write!(f, " endpoint=\"{}\"", escape_str_attribute(key))?;
+        }
+        write!(f, ">")?;
+        for sequence in &self.sequences {
+            write!(f, "{}", sequence)?;
+        }
+        if let Some(endpoint @ EndpointRef::Inline(_)) = &self.endpoint {
+            write!(f, "{}", endpoint)?;
+        }
+        write!(f, "</target>")
+    }
+}
+
+impl Display for PublishWsdl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<publishWSDL")?;
+        if let Some(uri) = &self.uri {
+            write!(f, " uri=\"{}\"", escape_str_attribute(uri))?;
+        }
+        if let Some(key) = &self.key {
+            write!(f, " key=\"{}\"", escape_str_attribute(key))?;
+        }
+        if let Some(preserve_policy) = &self.preserve_policy {
+            write!(f, " preservePolicy=\"{}\"", preserve_policy)?;
+        }
+        write!(f, ">")?;
+        if let Some(definition) = &self.definition {
+            write!(f, "{}", definition)?;
+        }
+        for resource in &self.resources {
+            write!(f, "<resource")?;
+            if let Some(location) = &resource.location {
+                write!(f, " location=\"{}\"", escape_str_attribute(location))?;
+            }
+            if let Some(key) = &resource.key {
+                write!(f, " key=\"{}\"", escape_str_attribute(key))?;
+            }
+            write!(f, "/>")?;
+        }
+        write!(f, "</publishWSDL>")
+    }
+}
+
+impl Display for MessageStore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<messageStore name=\"{}\"",
+            escape_str_attribute(&self.name)
+        )?;
+        if let Some(xmlns) = &self.xmlns {
+            write!(f, " xmlns=\"{}\"", escape_str_attribute(xmlns))?;
+        }
+        if let Some(class) = &self.class {
+            write!(f, " class=\"{}\"", escape_str_attribute(class))?;
+        }
+        write!(f, ">")?;
+        for parameter in &self.parameters {
+            write!(f, "{}", parameter)?;
+        }
+        write!(f, "</messageStore>")
+    }
+}
+
+impl Display for MessageProcessor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<messageProcessor name=\"{}\"",
+            escape_str_attribute(&self.name)
+        )?;
+        if let Some(xmlns) = &self.xmlns {
+            write!(f, " xmlns=\"{}\"", escape_str_attribute(xmlns))?;
+        }
+        write!(
+            f,
+            " class=\"{}\" messageStore=\"{}\"",
+            escape_str_attribute(&self.class),
+            escape_str_attribute(&self.message_store)
+        )?;
+        if let Some(target_endpoint) = &self.target_endpoint {
+            write!(
+                f,
+                " targetEndpoint=\"{}\"",
+                escape_str_attribute(target_endpoint)
+            )?;
+        }
+        write!(f, ">")?;
+        for parameter in &self.parameters {
+            write!(f, "{}", parameter)?;
+        }
+        write!(f, "</messageProcessor>")
+    }
+}
+
+impl Display for Parameter {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "<property name=\"{}\" value=\"{}\"/>",
-            self.name, self.value
+            "<parameter name=\"{}\">{}</parameter>",
+            escape_str_attribute(&self.name),
+            escape_str_pcdata(&self.value)
         )
     }
 }
+
+impl Display for Resource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let methods: Vec<String> = self
+            .methods
+            .iter()
+            .map(|method| method.to_string())
+            .collect();
+        write!(f, "<resource methods=\"{}\"", methods.join(" "))?;
+        if let Some(uri_template) = &self.uri_template {
+            write!(
+                f,
+                " uri-template=\"{}\"",
+                escape_str_attribute(uri_template)
+            )?;
+        }
+        if let Some(url_mapping) = &self.url_mapping {
+            write!(f, " url-mapping=\"{}\"", escape_str_attribute(url_mapping))?;
+        }
+        write!(f, ">")?;
+        for sequence in &self.sequences {
+            write!(f, "{}", sequence)?;
+        }
+        write!(f, "</resource>")
+    }
+}
+
+impl Display for HttpMethod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HttpMethod::Get => write!(f, "GET"),
+            HttpMethod::Post => write!(f, "POST"),
+            HttpMethod::Put => write!(f, "PUT"),
+            HttpMethod::Delete => write!(f, "DELETE"),
+            HttpMethod::Patch => write!(f, "PATCH"),
+            HttpMethod::Head => write!(f, "HEAD"),
+            HttpMethod::Options => write!(f, "OPTIONS"),
+        }
+    }
+}
+
+impl FromStr for HttpMethod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "GET" => Ok(HttpMethod::Get),
+            "POST" => Ok(HttpMethod::Post),
+            "PUT" => Ok(HttpMethod::Put),
+            "DELETE" => Ok(HttpMethod::Delete),
+            "PATCH" => Ok(HttpMethod::Patch),
+            "HEAD" => Ok(HttpMethod::Head),
+            "OPTIONS" => Ok(HttpMethod::Options),
+            _ => bail!("not a supported http method: {}", s),
+        }
+    }
+}
+
+impl Display for Toggle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Toggle::Enable => write!(f, "enable"),
+            Toggle::Disable => write!(f, "disable"),
+        }
+    }
+}
+
+impl FromStr for Toggle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "enable" => Ok(Toggle::Enable),
+            "disable" => Ok(Toggle::Disable),
+            _ => bail!("expected enable or disable, found {}", s),
+        }
+    }
+}
+
+impl Display for VersionType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VersionType::Url => write!(f, "url"),
+            VersionType::Context => write!(f, "context"),
+        }
+    }
+}
+
+impl FromStr for VersionType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "url" => Ok(VersionType::Url),
+            "context" => Ok(VersionType::Context),
+            _ => bail!("expected url or context, found {}", s),
+        }
+    }
+}
+
+impl Display for Scope {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Scope::Default => write!(f, "default"),
+            Scope::Transport => write!(f, "transport"),
+            Scope::Axis2 => write!(f, "axis2"),
+            Scope::Axis2Client => write!(f, "axis2-client"),
+            Scope::Operation => write!(f, "operation"),
+            Scope::Registry => write!(f, "registry"),
+        }
+    }
+}
+
+impl FromStr for Scope {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "default" => Ok(Scope::Default),
+            "transport" => Ok(Scope::Transport),
+            "axis2" => Ok(Scope::Axis2),
+            "axis2-client" => Ok(Scope::Axis2Client),
+            "operation" => Ok(Scope::Operation),
+            "registry" => Ok(Scope::Registry),
+            _ => bail!(
+                "expected default, transport, axis2, axis2-client, operation or registry, found {}",
+                s
+            ),
+        }
+    }
+}
+
+impl Display for HeaderAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HeaderAction::Set => write!(f, "set"),
+            HeaderAction::Remove => write!(f, "remove"),
+        }
+    }
+}
+
+impl FromStr for HeaderAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "set" => Ok(HeaderAction::Set),
+            "remove" => Ok(HeaderAction::Remove),
+            _ => bail!("expected set or remove, found {}", s),
+        }
+    }
+}
+
+impl Display for EnrichType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnrichType::Custom => write!(f, "custom"),
+            EnrichType::Envelope => write!(f, "envelope"),
+            EnrichType::Body => write!(f, "body"),
+            EnrichType::Property => write!(f, "property"),
+            EnrichType::Inline => write!(f, "inline"),
+            EnrichType::Key => write!(f, "key"),
+        }
+    }
+}
+
+impl FromStr for EnrichType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "custom" => Ok(EnrichType::Custom),
+            "envelope" => Ok(EnrichType::Envelope),
+            "body" => Ok(EnrichType::Body),
+            "property" => Ok(EnrichType::Property),
+            "inline" => Ok(EnrichType::Inline),
+            "key" => Ok(EnrichType::Key),
+            _ => bail!(
+                "expected custom, envelope, body, property, inline or key, found {}",
+                s
+            ),
+        }
+    }
+}
+
+impl Display for EnrichAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnrichAction::Replace => write!(f, "replace"),
+            EnrichAction::Child => write!(f, "child"),
+            EnrichAction::Sibling => write!(f, "sibling"),
+            EnrichAction::Remove => write!(f, "remove"),
+        }
+    }
+}
+
+impl FromStr for EnrichAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "replace" => Ok(EnrichAction::Replace),
+            "child" => Ok(EnrichAction::Child),
+            "sibling" => Ok(EnrichAction::Sibling),
+            "remove" => Ok(EnrichAction::Remove),
+            _ => bail!("expected replace, child, sibling or remove, found {}", s),
+        }
+    }
+}
+
+impl Display for Sequences {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Sequences::InSequence(in_sequence) => write!(f, "{}", in_sequence),
+            Sequences::OutSequence(out_sequence) => write!(f, "{}", out_sequence),
+            Sequences::FaultSequence(fault_sequence) => write!(f, "{}", fault_sequence),
+            Sequences::NamedSequence(named_sequence) => write!(f, "{}", named_sequence),
+        }
+    }
+}
+
+impl Display for InSequence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<inSequence>")?;
+        for mediator in &self.mediators {
+            write!(f, "{}", mediator)?;
+        }
+        write!(f, "</inSequence>")
+    }
+}
+
+impl Display for OutSequence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<outSequence>")?;
+        for mediator in &self.mediators {
+            write!(f, "{}", mediator)?;
+        }
+        write!(f, "</outSequence>")
+    }
+}
+
+impl Display for FaultSequence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<faultSequence>")?;
+        for mediator in &self.mediators {
+            write!(f, "{}", mediator)?;
+        }
+        write!(f, "</faultSequence>")
+    }
+}
+
+impl Display for NamedSequence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<sequence name=\"{}\"", escape_str_attribute(&self.name))?;
+        if let Some(xmlns) = &self.xmlns {
+            write!(f, " xmlns=\"{}\"", escape_str_attribute(xmlns))?;
+        }
+        if let Some(on_error) = &self.on_error {
+            write!(f, " onError=\"{}\"", escape_str_attribute(on_error))?;
+        }
+        if let Some(trace) = &self.trace {
+            write!(f, " trace=\"{}\"", trace)?;
+        }
+        if let Some(statistics) = &self.statistics {
+            write!(f, " statistics=\"{}\"", statistics)?;
+        }
+        write!(f, ">")?;
+        for mediator in &self.mediators {
+            write!(f, "{}", mediator)?;
+        }
+        write!(f, "</sequence>")
+    }
+}
+
+impl Display for Mediators {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mediators::Log(log_mediator) => write!(f, "{}", log_mediator),
+            Mediators::Property(property_mediator) => write!(f, "{}", property_mediator),
+            Mediators::Call(call_mediator) => write!(f, "{}", call_mediator),
+            Mediators::Send(send_mediator) => write!(f, "{}", send_mediator),
+            Mediators::Callout(callout_mediator) => write!(f, "{}", callout_mediator),
+            Mediators::Store(store_mediator) => write!(f, "{}", store_mediator),
+            Mediators::Makefault(makefault_mediator) => write!(f, "{}", makefault_mediator),
+            Mediators::Filter(filter_mediator) => write!(f, "{}", filter_mediator),
+            Mediators::Switch(switch_mediator) => write!(f, "{}", switch_mediator),
+            Mediators::PayloadFactory(payload_factory) => write!(f, "{}", payload_factory),
+            Mediators::Header(header_mediator) => write!(f, "{}", header_mediator),
+            Mediators::Class(class_mediator) => write!(f, "{}", class_mediator),
+            Mediators::Enrich(enrich_mediator) => write!(f, "{}", enrich_mediator),
+            Mediators::Xslt(xslt_mediator) => write!(f, "{}", xslt_mediator),
+            Mediators::Xquery(xquery_mediator) => write!(f, "{}", xquery_mediator),
+            Mediators::Validate(validate_mediator) => write!(f, "{}", validate_mediator),
+            Mediators::Script(script_mediator) => write!(f, "{}", script_mediator),
+            Mediators::Iterate(iterate_mediator) => write!(f, "{}", iterate_mediator),
+            Mediators::Aggregate(aggregate_mediator) => write!(f, "{}", aggregate_mediator),
+            Mediators::Clone(clone_mediator) => write!(f, "{}", clone_mediator),
+            Mediators::Cache(cache_mediator) => write!(f, "{}", cache_mediator),
+            Mediators::Sequence(sequence_mediator) => {
+                write!(
+                    f,
+                    "<sequence key=\"{}\"/>",
+                    escape_str_attribute(&sequence_mediator.key)
+                )
+            }
+            Mediators::Respond | Mediators::Drop | Mediators::Loopback => {
+                write!(f, "<{}/>", self.name())
+            }
+            Mediators::Extension(extension) => write!(f, "{}", extension),
+            Mediators::Unparsed(element) => write!(f, "{}", element),
+        }
+    }
+}
+
+impl Display for LogMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<log level=\"{}\">", self.level)?;
+        for property in &self.properties {
+            write!(f, "{}", property)?;
+        }
+        write!(f, "</log>")
+    }
+}
+
+impl Display for PropertyMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<property name=\"{}\"", escape_str_attribute(&self.name))?;
+        if let Some(action) = &self.action {
+            write!(f, " action=\"{}\"", action)?;
+        }
+        match (&self.expression, self.action) {
+            (Some(expression), _) => {
+                write!(f, " expression=\"{}\"", escape_str_attribute(expression))?
+            }
+            //removed properties have no value, OM properties may have an inline value instead
+            (None, Some(HeaderAction::Remove)) => {}
+            (None, _) if !self.inline.is_empty() && self.value.is_empty() => {}
+            (None, _) => write!(f, " value=\"{}\"", escape_str_attribute(&self.value))?,
+        }
+        if let Some(scope) = &self.scope {
+            write!(f, " scope=\"{}\"", scope)?;
+        }
+        if let Some(kind) = &self.kind {
+            write!(f, " type=\"{}\"", escape_str_attribute(kind))?;
+        }
+        if let Some(pattern) = &self.pattern {
+            write!(f, " pattern=\"{}\"", escape_str_attribute(pattern))?;
+        }
+        if let Some(group) = &self.group {
+            write!(f, " group=\"{}\"", group)?;
+        }
+        if self.inline.is_empty() {
+            return write!(f, "/>");
+        }
+
+        write!(f, ">")?;
+        for node in &self.inline {
+            write!(f, "{}", node)?;
+        }
+        write!(f, "</property>")
+    }
+}
+
+impl Display for CallMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<call")?;
+        if let Some(blocking) = &self.blocking {
+            write!(f, " blocking=\"{}\"", blocking)?;
+        }
+        write!(f, ">")?;
+        if let Some(endpoint) = &self.endpoint {
+            write!(f, "{}", endpoint)?;
+        }
+        write!(f, "</call>")
+    }
+}
+
+impl Display for FilterMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<filter {}><then>", self.condition)?;
+        for mediator in &self.then_mediators {
+            write!(f, "{}", mediator)?;
+        }
+        write!(f, "</then>")?;
+        if let Some(else_mediators) = &self.else_mediators {
+            write!(f, "<else>")?;
+            for mediator in else_mediators {
+                write!(f, "{}", mediator)?;
+            }
+            write!(f, "</else>")?;
+        }
+        write!(f, "</filter>")
+    }
+}
+
+impl Display for FilterCondition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterCondition::Xpath(xpath) => {
+                write!(f, "xpath=\"{}\"", escape_str_attribute(xpath))
+            }
+            FilterCondition::SourceRegex { source, regex } => write!(
+                f,
+                "source=\"{}\" regex=\"{}\"",
+                escape_str_attribute(source),
+                escape_str_attribute(regex)
+            ),
+        }
+    }
+}
+
+impl Display for SwitchMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<switch source=\"{}\">",
+            escape_str_attribute(&self.source)
+        )?;
+        for case in &self.cases {
+            write!(f, "{}", case)?;
+        }
+        if let Some(default) = &self.default {
+            write!(f, "<default>")?;
+            for mediator in default {
+                write!(f, "{}", mediator)?;
+            }
+            write!(f, "</default>")?;
+        }
+        write!(f, "</switch>")
+    }
+}
+
+impl Display for SwitchCase {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<case regex=\"{}\">", escape_str_attribute(&self.regex))?;
+        for mediator in &self.mediators {
+            write!(f, "{}", mediator)?;
+        }
+        write!(f, "</case>")
+    }
+}
+
+impl Display for PayloadFactoryMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<payloadFactory")?;
+        if let Some(media_type) = &self.media_type {
+            write!(f, " media-type=\"{}\"", escape_str_attribute(media_type))?;
+        }
+        if let Some(template_type) = &self.template_type {
+            write!(
+                f,
+                " template-type=\"{}\"",
+                escape_str_attribute(template_type)
+            )?;
+        }
+        write!(f, ">")?;
+        match &self.format {
+            PayloadFormat::Key(key) => {
+                write!(f, "<format key=\"{}\"/>", escape_str_attribute(key))?
+            }
+            PayloadFormat::Inline(content) => {
+                write!(f, "<format>")?;
+                for node in content {
+                    write!(f, "{}", node)?;
+                }
+                write!(f, "</format>")?;
+            }
+        }
+        write!(f, "<args>")?;
+        for arg in &self.args {
+            write!(f, "{}", arg)?;
+        }
+        write!(f, "</args></payloadFactory>")
+    }
+}
+
+impl Display for PayloadArg {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PayloadArg::Value(value) => {
+                write!(f, "<arg value=\"{}\"/>", escape_str_attribute(value))
+            }
+            PayloadArg::Expression {
+                expression,
+                evaluator,
+                literal,
+            } => {
+                write!(f, "<arg")?;
+                if let Some(evaluator) = evaluator {
+                    write!(f, " evaluator=\"{}\"", escape_str_attribute(evaluator))?;
+                }
+                write!(f, " expression=\"{}\"", escape_str_attribute(expression))?;
+                if let Some(literal) = literal {
+                    write!(f, " literal=\"{}\"", literal)?;
+                }
+                write!(f, "/>")
+            }
+        }
+    }
+}
+
+impl Display for HeaderMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<header")?;
+        if let Some(name) = &self.name {
+            write!(f, " name=\"{}\"", escape_str_attribute(name))?;
+        }
+        match &self.value {
+            Some(HeaderValue::Value(value)) => {
+                write!(f, " value=\"{}\"", escape_str_attribute(value))?
+            }
+            Some(HeaderValue::Expression(expression)) => {
+                write!(f, " expression=\"{}\"", escape_str_attribute(expression))?
+            }
+            Some(HeaderValue::Inline(_)) | None => {}
+        }
+        if let Some(action) = &self.action {
+            write!(f, " action=\"{}\"", action)?;
+        }
+        if let Some(scope) = &self.scope {
+            write!(f, " scope=\"{}\"", scope)?;
+        }
+        match &self.value {
+            Some(HeaderValue::Inline(element)) => write!(f, ">{}</header>", element),
+            _ => write!(f, "/>"),
+        }
+    }
+}
+
+impl Display for ClassMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.properties.is_empty() {
+            return write!(f, "<class name=\"{}\"/>", escape_str_attribute(&self.name));
+        }
+
+        write!(f, "<class name=\"{}\">", escape_str_attribute(&self.name))?;
+        for property in &self.properties {
+            write!(f, "{}", property)?;
+        }
+        write!(f, "</class>")
+    }
+}
+
+impl Display for EnrichMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<enrich>{}{}</enrich>", self.source, self.target)
+    }
+}
+
+impl Display for EnrichSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<source")?;
+        if let Some(clone) = &self.clone {
+            write!(f, " clone=\"{}\"", clone)?;
+        }
+        if let Some(kind) = &self.kind {
+            write!(f, " type=\"{}\"", kind)?;
+        }
+        if let Some(xpath) = &self.xpath {
+            write!(f, " xpath=\"{}\"", escape_str_attribute(xpath))?;
+        }
+        if let Some(property) = &self.property {
+            write!(f, " property=\"{}\"", escape_str_attribute(property))?;
+        }
+        if let Some(key) = &self.key {
+            write!(f, " key=\"{}\"", escape_str_attribute(key))?;
+        }
+        if self.inline.is_empty() {
+            return write!(f, "/>");
+        }
+
+        write!(f, ">")?;
+        for node in &self.inline {
+            write!(f, "{}", node)?;
+        }
+        write!(f, "</source>")
+    }
+}
+
+impl Display for EnrichTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<target")?;
+        if let Some(action) = &self.action {
+            write!(f, " action=\"{}\"", action)?;
+        }
+        if let Some(kind) = &self.kind {
+            write!(f, " type=\"{}\"", kind)?;
+        }
+        if let Some(xpath) = &self.xpath {
+            write!(f, " xpath=\"{}\"", escape_str_attribute(xpath))?;
+        }
+        if let Some(property) = &self.property {
+            write!(f, " property=\"{}\"", escape_str_attribute(property))?;
+        }
+        write!(f, "/>")
+    }
+}
+
+impl Display for XsltMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<xslt key=\"{}\"", escape_str_attribute(&self.key))?;
+        if let Some(source) = &self.source {
+            write!(f, " source=\"{}\"", escape_str_attribute(source))?;
+        }
+        write!(f, ">")?;
+        for property in &self.properties {
+            write!(f, "{}", property)?;
+        }
+        for feature in &self.features {
+            write!(
+                f,
+                "<feature name=\"{}\" value=\"{}\"/>",
+                escape_str_attribute(&feature.name),
+                feature.value
+            )?;
+        }
+        write!(f, "</xslt>")
+    }
+}
+
+impl Display for XqueryMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<xquery key=\"{}\"", escape_str_attribute(&self.key))?;
+        if let Some(target) = &self.target {
+            write!(f, " target=\"{}\"", escape_str_attribute(target))?;
+        }
+        write!(f, ">")?;
+        for variable in &self.variables {
+            write!(f, "{}", variable)?;
+        }
+        write!(f, "</xquery>")
+    }
+}
+
+impl Display for XqueryVariable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<variable name=\"{}\" type=\"{}\"",
+            escape_str_attribute(&self.name),
+            escape_str_attribute(&self.kind)
+        )?;
+        if let Some(value) = &self.value {
+            write!(f, " value=\"{}\"", escape_str_attribute(value))?;
+        }
+        if let Some(expression) = &self.expression {
+            write!(f, " expression=\"{}\"", escape_str_attribute(expression))?;
+        }
+        if let Some(key) = &self.key {
+            write!(f, " key=\"{}\"", escape_str_attribute(key))?;
+        }
+        write!(f, "/>")
+    }
+}
+
+impl Display for ValidateMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<validate")?;
+        if let Some(source) = &self.source {
+            write!(f, " source=\"{}\"", escape_str_attribute(source))?;
+        }
+        write!(f, ">")?;
+        for schema in &self.schemas {
+            write!(f, "<schema key=\"{}\"/>", escape_str_attribute(schema))?;
+        }
+        for property in &self.properties {
+            write!(f, "{}", property)?;
+        }
+        write!(f, "<on-fail>")?;
+        for mediator in &self.on_fail {
+            write!(f, "{}", mediator)?;
+        }
+        write!(f, "</on-fail></validate>")
+    }
+}
+
+impl Display for ScriptMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<script language=\"{}\"",
+            escape_str_attribute(&self.language)
+        )?;
+        if let Some(key) = &self.key {
+            write!(f, " key=\"{}\"", escape_str_attribute(key))?;
+        }
+        if let Some(function) = &self.function {
+            write!(f, " function=\"{}\"", escape_str_attribute(function))?;
+        }
+        match &self.body {
+            //]]> cannot appear inside a cdata section, it is split over two sections
+            Some(body) => write!(
+                f,
+                "><![CDATA[{}]]></script>",
+                body.replace("]]>", "]]]]><![CDATA[>")
+            ),
+            None => write!(f, "/>"),
+        }
+    }
+}
+
+impl Display for IterateMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<iterate")?;
+        if let Some(id) = &self.id {
+            write!(f, " id=\"{}\"", escape_str_attribute(id))?;
+        }
+        write!(
+            f,
+            " expression=\"{}\"",
+            escape_str_attribute(&self.expression)
+        )?;
+        if let Some(attach_path) = &self.attach_path {
+            write!(f, " attachPath=\"{}\"", escape_str_attribute(attach_path))?;
+        }
+        if let Some(preserve_payload) = &self.preserve_payload {
+            write!(f, " preservePayload=\"{}\"", preserve_payload)?;
+        }
+        if let Some(sequential) = &self.sequential {
+            write!(f, " sequential=\"{}\"", sequential)?;
+        }
+        if let Some(continue_parent) = &self.continue_parent {
+            write!(f, " continueParent=\"{}\"", continue_parent)?;
+        }
+        write!(f, ">{}</iterate>", self.target)
+    }
+}
+
+impl Display for CloneMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<clone")?;
+        if let Some(id) = &self.id {
+            write!(f, " id=\"{}\"", escape_str_attribute(id))?;
+        }
+        if let Some(continue_parent) = &self.continue_parent {
+            write!(f, " continueParent=\"{}\"", continue_parent)?;
+        }
+        if let Some(sequential) = &self.sequential {
+            write!(f, " sequential=\"{}\"", sequential)?;
+        }
+        write!(f, ">")?;
+        for target in &self.targets {
+            write!(f, "{}", target)?;
+        }
+        write!(f, "</clone>")
+    }
+}
+
+impl Display for CacheMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<cache")?;
+        if let Some(id) = &self.id {
+            write!(f, " id=\"{}\"", escape_str_attribute(id))?;
+        }
+        if let Some(collector) = &self.collector {
+            write!(f, " collector=\"{}\"", collector)?;
+        }
+        if let Some(timeout) = &self.timeout {
+            write!(f, " timeout=\"{}\"", timeout)?;
+        }
+        if let Some(max_message_size) = &self.max_message_size {
+            write!(f, " maxMessageSize=\"{}\"", max_message_size)?;
+        }
+        write!(f, ">")?;
+        if let Some(on_cache_hit) = &self.on_cache_hit {
+            write!(f, "<onCacheHit")?;
+            if let Some(sequence) = &on_cache_hit.sequence {
+                write!(f, " sequence=\"{}\"", escape_str_attribute(sequence))?;
+            }
+            write!(f, ">")?;
+            for mediator in &on_cache_hit.mediators {
+                write!(f, "{}", mediator)?;
+            }
+            write!(f, "</onCacheHit>")?;
+        }
+        if let Some(protocol) = &self.protocol {
+            write!(f, "{}", protocol)?;
+        }
+        if let Some(implementation) = &self.implementation {
+            write!(f, "<implementation")?;
+            if let Some(kind) = &implementation.kind {
+                write!(f, " type=\"{}\"", escape_str_attribute(kind))?;
+            }
+            if let Some(max_size) = &implementation.max_size {
+                write!(f, " maxSize=\"{}\"", max_size)?;
+            }
+            write!(f, "/>")?;
+        }
+        write!(f, "</cache>")
+    }
+}
+
+impl Display for CacheProtocol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<protocol type=\"{}\">",
+            escape_str_attribute(&self.kind)
+        )?;
+        let settings = [
+            ("methods", self.methods.clone()),
+            (
+                "headersToExcludeInHash",
+                self.headers_to_exclude_in_hash.clone(),
+            ),
+            (
+                "headersToIncludeInHash",
+                self.headers_to_include_in_hash.clone(),
+            ),
+            ("responseCodes", self.response_codes.clone()),
+            (
+                "enableCacheControl",
+                self.enable_cache_control.map(|value| value.to_string()),
+            ),
+            (
+                "includeAgeHeader",
+                self.include_age_header.map(|value| value.to_string()),
+            ),
+            ("hashGenerator", self.hash_generator.clone()),
+        ];
+        for (name, value) in settings {
+            if let Some(value) = value {
+                write!(f, "<{0}>{1}</{0}>", name, escape_str_pcdata(&value))?;
+            }
+        }
+        write!(f, "</protocol>")
+    }
+}
+
+impl Display for AggregateMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<aggregate")?;
+        if let Some(id) = &self.id {
+            write!(f, " id=\"{}\"", escape_str_attribute(id))?;
+        }
+        write!(f, ">")?;
+        if let Some(correlate_on) = &self.correlate_on {
+            write!(
+                f,
+                "<correlateOn expression=\"{}\"/>",
+                escape_str_attribute(correlate_on)
+            )?;
+        }
+        if let Some(complete_condition) = &self.complete_condition {
+            write!(f, "{}", complete_condition)?;
+        }
+        write!(f, "{}</aggregate>", self.on_complete)
+    }
+}
+
+impl Display for CompleteCondition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<completeCondition")?;
+        if let Some(timeout) = &self.timeout {
+            write!(f, " timeout=\"{}\"", timeout)?;
+        }
+        write!(f, "><messageCount")?;
+        if let Some(min_messages) = &self.min_messages {
+            write!(f, " min=\"{}\"", escape_str_attribute(min_messages))?;
+        }
+        if let Some(max_messages) = &self.max_messages {
+            write!(f, " max=\"{}\"", escape_str_attribute(max_messages))?;
+        }
+        write!(f, "/></completeCondition>")
+    }
+}
+
+impl Display for OnComplete {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<onComplete expression=\"{}\"",
+            escape_str_attribute(&self.expression)
+        )?;
+        if let Some(sequence) = &self.sequence {
+            write!(f, " sequence=\"{}\"", escape_str_attribute(sequence))?;
+        }
+        if let Some(property) = &self.enclosing_element_property {
+            write!(
+                f,
+                " enclosingElementProperty=\"{}\"",
+                escape_str_attribute(property)
+            )?;
+        }
+        write!(f, ">")?;
+        for mediator in &self.mediators {
+            write!(f, "{}", mediator)?;
+        }
+        write!(f, "</onComplete>")
+    }
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<target")?;
+        if let Some(to) = &self.to {
+            write!(f, " to=\"{}\"", escape_str_attribute(to))?;
+        }
+        if let Some(soap_action) = &self.soap_action {
+            write!(f, " soapAction=\"{}\"", escape_str_attribute(soap_action))?;
+        }
+        if let Some(SequenceRef::Key(key)) = &self.sequence {
+            write!(f, " sequence=\"{}\"", escape_str_attribute(key))?;
+        }
+        if let Some(EndpointRef::Key(key)) = &self.endpoint {
+            write!(f, " endpoint=\"{}\"", escape_str_attribute(key))?;
+        }
+        write!(f, ">")?;
+        if let Some(SequenceRef::Inline(mediators)) = &self.sequence {
+            write!(f, "<sequence>")?;
+            for mediator in mediators {
+                write!(f, "{}", mediator)?;
+            }
+            write!(f, "</sequence>")?;
+        }
+        if let Some(EndpointRef::Inline(endpoint)) = &self.endpoint {
+            write!(f, "{}", endpoint)?;
+        }
+        write!(f, "</target>")
+    }
+}
+
+impl Display for SendMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<send")?;
+        if let Some(receive) = &self.receive {
+            write!(f, " receive=\"{}\"", escape_str_attribute(receive))?;
+        }
+        if let Some(build_message) = &self.build_message {
+            write!(f, " buildmessage=\"{}\"", build_message)?;
+        }
+        match &self.endpoint {
+            Some(endpoint) => write!(f, ">{}</send>", endpoint),
+            None => write!(f, "/>"),
+        }
+    }
+}
+
+impl Display for CalloutMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<callout")?;
+        if let Some(service_url) = &self.service_url {
+            write!(f, " serviceURL=\"{}\"", escape_str_attribute(service_url))?;
+        }
+        if let Some(endpoint_key) = &self.endpoint_key {
+            write!(f, " endpointKey=\"{}\"", escape_str_attribute(endpoint_key))?;
+        }
+        if let Some(action) = &self.action {
+            write!(f, " action=\"{}\"", escape_str_attribute(action))?;
+        }
+        if let Some(init) = &self.init_axis2_client_options {
+            write!(f, " initAxis2ClientOptions=\"{}\"", init)?;
+        }
+        write!(f, ">")?;
+        if let Some(configuration) = &self.configuration {
+            write!(f, "<configuration")?;
+            if let Some(axis2xml) = &configuration.axis2xml {
+                write!(f, " axis2xml=\"{}\"", escape_str_attribute(axis2xml))?;
+            }
+            if let Some(repository) = &configuration.repository {
+                write!(f, " repository=\"{}\"", escape_str_attribute(repository))?;
+            }
+            write!(f, "/>")?;
+        }
+        if let Some(source) = &self.source {
+            write!(f, "<source")?;
+            write_namespaces(f, &source.namespaces)?;
+            if let Some(xpath) = &source.xpath {
+                write!(f, " xpath=\"{}\"", escape_str_attribute(xpath))?;
+            }
+            if let Some(key) = &source.key {
+                write!(f, " key=\"{}\"", escape_str_attribute(key))?;
+            }
+            if let Some(kind) = &source.kind {
+                write!(f, " type=\"{}\"", escape_str_attribute(kind))?;
+            }
+            write!(f, "/>")?;
+        }
+        if let Some(target) = &self.target {
+            write!(f, "<target")?;
+            write_namespaces(f, &target.namespaces)?;
+            if let Some(xpath) = &target.xpath {
+                write!(f, " xpath=\"{}\"", escape_str_attribute(xpath))?;
+            }
+            if let Some(key) = &target.key {
+                write!(f, " key=\"{}\"", escape_str_attribute(key))?;
+            }
+            write!(f, "/>")?;
+        }
+        if let Some(enable_sec) = &self.enable_sec {
+            write!(f, "{}", enable_sec)?;
+        }
+        write!(f, "</callout>")
+    }
+}
+
+impl Display for StoreMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "<store messageStore=\"{}\"",
+            escape_str_attribute(&self.message_store)
+        )?;
+        if let Some(sequence) = &self.sequence {
+            write!(f, " sequence=\"{}\"", escape_str_attribute(sequence))?;
+        }
+        write!(f, "/>")
+    }
+}
+
+impl Display for MakefaultMediator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<makefault")?;
+        write_namespaces(f, &self.namespaces)?;
+        if let Some(version) = &self.version {
+            write!(f, " version=\"{}\"", version)?;
+        }
+        if let Some(response) = &self.response {
+            write!(f, " response=\"{}\"", response)?;
+        }
+        write!(f, ">")?;
+        if let Some(code) = &self.code {
+            write!(f, "<code{}/>", code)?;
+        }
+        write!(f, "<reason{}/>", self.reason)?;
+        if let Some(node) = &self.node {
+            write!(f, "<node>{}</node>", escape_str_pcdata(node))?;
+        }
+        if let Some(role) = &self.role {
+            write!(f, "<role>{}</role>", escape_str_pcdata(role))?;
+        }
+        if let Some(detail) = &self.detail {
+            write!(f, "<detail")?;
+            if let Some(expression) = &detail.expression {
+                write!(f, " expression=\"{}\"", escape_str_attribute(expression))?;
+            }
+            if detail.inline.is_empty() {
+                write!(f, "/>")?;
+            } else {
+                write!(f, ">")?;
+                for node in &detail.inline {
+                    write!(f, "{}", node)?;
+                }
+                write!(f, "</detail>")?;
+            }
+        }
+        write!(f, "</makefault>")
+    }
+}
+
+//written as the value or expression attribute of the enclosing element
+impl Display for FaultValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FaultValue::Value(value) => write!(f, " value=\"{}\"", escape_str_attribute(value)),
+            FaultValue::Expression(expression) => {
+                write!(f, " expression=\"{}\"", escape_str_attribute(expression))
+            }
+        }
+    }
+}
+
+impl Display for FaultVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FaultVersion::Soap11 => write!(f, "soap11"),
+            FaultVersion::Soap12 => write!(f, "soap12"),
+            FaultVersion::Pox => write!(f, "pox"),
+        }
+    }
+}
+
+impl FromStr for FaultVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "soap11" => Ok(FaultVersion::Soap11),
+            "soap12" => Ok(FaultVersion::Soap12),
+            "pox" => Ok(FaultVersion::Pox),
+            _ => bail!("expected soap11, soap12 or pox, found {}", s),
+        }
+    }
+}
+
+impl Display for EnableSec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<enableSec")?;
+        if let Some(policy) = &self.policy {
+            write!(f, " policy=\"{}\"", escape_str_attribute(policy))?;
+        }
+        if let Some(outbound_policy) = &self.outbound_policy {
+            write!(
+                f,
+                " outboundPolicy=\"{}\"",
+                escape_str_attribute(outbound_policy)
+            )?;
+        }
+        if let Some(inbound_policy) = &self.inbound_policy {
+            write!(
+                f,
+                " inboundPolicy=\"{}\"",
+                escape_str_attribute(inbound_policy)
+            )?;
+        }
+        write!(f, "/>")
+    }
+}
+
+impl Display for EndpointRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EndpointRef::Key(key) => {
+                write!(f, "<endpoint key=\"{}\"/>", escape_str_attribute(key))
+            }
+            EndpointRef::Inline(endpoint) => write!(f, "{}", endpoint),
+        }
+    }
+}
+
+impl RawElement {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    //child elements, text content is skipped
+    pub fn elements(&self) -> impl Iterator<Item = &RawElement> {
+        self.children.iter().filter_map(|child| match child {
+            RawNode::Element(element) => Some(element),
+            RawNode::Text(_) | RawNode::CData(_) => None,
+        })
+    }
+
+    //concatenated text content of the element and its children
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for child in &self.children {
+            match child {
+                RawNode::Element(element) => text.push_str(&element.text()),
+                RawNode::Text(content) | RawNode::CData(content) => text.push_str(content),
+            }
+        }
+        text
+    }
+}
+
+impl Display for RawElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match &self.prefix {
+            Some(prefix) => format!("{}:{}", prefix, self.name),
+            None => self.name.clone(),
+        };
+
+        write!(f, "<{}", name)?;
+        write_namespaces(f, &self.namespaces)?;
+        for (attribute, value) in &self.attributes {
+            write!(f, " {}=\"{}\"", attribute, escape_str_attribute(value))?;
+        }
+        if self.children.is_empty() {
+            return write!(f, "/>");
+        }
+
+        write!(f, ">")?;
+        for child in &self.children {
+            write!(f, "{}", child)?;
+        }
+        write!(f, "</{}>", name)
+    }
+}
+
+//namespace declarations as xmlns attributes, the prefix is empty for the default namespace
+fn write_namespaces(f: &mut Formatter<'_>, namespaces: &[(String, String)]) -> std::fmt::Result {
+    for (prefix, uri) in namespaces {
+        match prefix.as_str() {
+            "" => write!(f, " xmlns=\"{}\"", escape_str_attribute(uri))?,
+            _ => write!(f, " xmlns:{}=\"{}\"", prefix, escape_str_attribute(uri))?,
+        }
+    }
+    Ok(())
+}
+
+impl Display for RawNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RawNode::Element(element) => write!(f, "{}", element),
+            RawNode::Text(text) => write!(f, "{}", escape_str_pcdata(text)),
+            //]]> cannot appear inside a cdata section, it is split over two sections
+            RawNode::CData(text) => {
+                write!(f, "<![CDATA[{}]]>", text.replace("]]>", "]]]]><![CDATA[>"))
+            }
+        }
+    }
+}
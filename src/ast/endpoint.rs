@@ -0,0 +1,458 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+use anyhow::bail;
+use xml::escape::{escape_str_attribute, escape_str_pcdata};
+
+use super::{EndpointRef, HttpMethod, PropertyMediator};
+
+#[derive(Debug)]
+pub struct Endpoint {
+    pub name: Option<String>,
+    //only set on endpoint artifacts, inline endpoints inherit the namespace of their parent
+    pub xmlns: Option<String>,
+    pub kind: EndpointKind,
+    //properties set on the message when it is sent to the endpoint
+    pub properties: Vec<PropertyMediator>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum EndpointKind {
+    Http(HttpEndpoint),
+    Address(AddressEndpoint),
+    Wsdl(WsdlEndpoint),
+    Default(DefaultEndpoint),
+    Loadbalance(LoadbalanceEndpoint),
+    Failover(FailoverEndpoint),
+    RecipientList(RecipientListEndpoint),
+}
+
+#[derive(Debug)]
+pub struct HttpEndpoint {
+    pub method: Option<HttpMethod>,
+    pub uri_template: String,
+    pub qos: Qos,
+}
+
+#[derive(Debug)]
+pub struct AddressEndpoint {
+    pub uri: String,
+    pub format: Option<EndpointFormat>,
+    pub optimize: Option<Optimize>,
+    pub qos: Qos,
+}
+
+#[derive(Debug)]
+pub struct WsdlEndpoint {
+    pub uri: String,
+    pub service: Option<String>,
+    pub port: Option<String>,
+    pub qos: Qos,
+}
+
+//sends the message to the address of the message itself
+#[derive(Debug)]
+pub struct DefaultEndpoint {
+    pub format: Option<EndpointFormat>,
+    pub optimize: Option<Optimize>,
+    pub qos: Qos,
+}
+
+//message format the endpoint converts the message to before sending it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointFormat {
+    Soap11,
+    Soap12,
+    Pox,
+    Get,
+    Rest,
+}
+
+//attachment optimization of soap messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Optimize {
+    Mtom,
+    Swa,
+}
+
+#[derive(Debug)]
+pub struct LoadbalanceEndpoint {
+    pub algorithm: Option<String>,
+    pub failover: Option<bool>,
+    pub members: Vec<EndpointRef>,
+}
+
+#[derive(Debug)]
+pub struct FailoverEndpoint {
+    pub build_message: Option<bool>,
+    pub members: Vec<EndpointRef>,
+}
+
+#[derive(Debug)]
+pub struct RecipientListEndpoint {
+    pub members: Vec<EndpointRef>,
+}
+
+//timeout and suspension settings shared by all leaf endpoints
+#[derive(Debug, Default)]
+pub struct Qos {
+    pub timeout: Option<Timeout>,
+    pub suspend_on_failure: Option<SuspendOnFailure>,
+    pub mark_for_suspension: Option<MarkForSuspension>,
+}
+
+//durations are in milliseconds
+#[derive(Debug, Default)]
+pub struct Timeout {
+    pub duration: Option<u64>,
+    pub response_action: Option<ResponseAction>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseAction {
+    Fault,
+    Discard,
+    Never,
+}
+
+#[derive(Debug, Default)]
+pub struct SuspendOnFailure {
+    pub error_codes: Vec<i32>,
+    pub initial_duration: Option<u64>,
+    pub progression_factor: Option<f64>,
+    pub maximum_duration: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+pub struct MarkForSuspension {
+    pub error_codes: Vec<i32>,
+    pub retries_before_suspension: Option<u32>,
+    pub retry_delay: Option<u64>,
+}
+
+impl Endpoint {
+    //serialized endpoint without name and namespace, equal for endpoints with the same definition
+    pub fn definition(&self) -> String {
+        let mut definition = self.kind.to_string();
+        for property in &self.properties {
+            definition.push_str(&property.to_string());
+        }
+        if let Some(description) = &self.description {
+            definition.push_str(&escape_str_pcdata(description));
+        }
+        definition
+    }
+
+    //members of group endpoints, leaf endpoints have none
+    pub fn members(&self) -> &[EndpointRef] {
+        match &self.kind {
+            EndpointKind::Loadbalance(LoadbalanceEndpoint { members, .. })
+            | EndpointKind::Failover(FailoverEndpoint { members, .. })
+            | EndpointKind::RecipientList(RecipientListEndpoint { members }) => members,
+            _ => &[],
+        }
+    }
+
+    //timeout and suspension settings, group endpoints have none of their own
+    pub fn qos(&self) -> Option<&Qos> {
+        match &self.kind {
+            EndpointKind::Http(HttpEndpoint { qos, .. })
+            | EndpointKind::Address(AddressEndpoint { qos, .. })
+            | EndpointKind::Wsdl(WsdlEndpoint { qos, .. })
+            | EndpointKind::Default(DefaultEndpoint { qos, .. }) => Some(qos),
+            EndpointKind::Loadbalance(_)
+            | EndpointKind::Failover(_)
+            | EndpointKind::RecipientList(_) => None,
+        }
+    }
+}
+
+//--------------------------------------------------------------------------------//
+impl Display for Endpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<endpoint")?;
+        if let Some(name) = &self.name {
+            write!(f, " name=\"{}\"", escape_str_attribute(name))?;
+        }
+        if let Some(xmlns) = &self.xmlns {
+            write!(f, " xmlns=\"{}\"", escape_str_attribute(xmlns))?;
+        }
+        write!(f, ">{}", self.kind)?;
+        for property in &self.properties {
+            write!(f, "{}", property)?;
+        }
+        if let Some(description) = &self.description {
+            write!(
+                f,
+                "<description>{}</description>",
+                escape_str_pcdata(description)
+            )?;
+        }
+        write!(f, "</endpoint>")
+    }
+}
+
+impl Display for EndpointKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EndpointKind::Http(http_endpoint) => write!(f, "{}", http_endpoint),
+            EndpointKind::Address(address_endpoint) => write!(f, "{}", address_endpoint),
+            EndpointKind::Wsdl(wsdl_endpoint) => write!(f, "{}", wsdl_endpoint),
+            EndpointKind::Default(default_endpoint) => write!(f, "{}", default_endpoint),
+            EndpointKind::Loadbalance(loadbalance_endpoint) => {
+                write!(f, "{}", loadbalance_endpoint)
+            }
+            EndpointKind::Failover(failover_endpoint) => write!(f, "{}", failover_endpoint),
+            EndpointKind::RecipientList(recipient_list_endpoint) => {
+                write!(f, "{}", recipient_list_endpoint)
+            }
+        }
+    }
+}
+
+impl Display for HttpEndpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<http")?;
+        if let Some(method) = &self.method {
+            write!(f, " method=\"{}\"", method)?;
+        }
+        write!(
+            f,
+            " uri-template=\"{}\">{}</http>",
+            escape_str_attribute(&self.uri_template),
+            self.qos
+        )
+    }
+}
+
+impl Display for AddressEndpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<address uri=\"{}\"", escape_str_attribute(&self.uri))?;
+        write_format(f, &self.format, &self.optimize)?;
+        write!(f, ">{}</address>", self.qos)
+    }
+}
+
+impl Display for WsdlEndpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<wsdl uri=\"{}\"", escape_str_attribute(&self.uri))?;
+        if let Some(service) = &self.service {
+            write!(f, " service=\"{}\"", escape_str_attribute(service))?;
+        }
+        if let Some(port) = &self.port {
+            write!(f, " port=\"{}\"", escape_str_attribute(port))?;
+        }
+        write!(f, ">{}</wsdl>", self.qos)
+    }
+}
+
+impl Display for DefaultEndpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<default")?;
+        write_format(f, &self.format, &self.optimize)?;
+        write!(f, ">{}</default>", self.qos)
+    }
+}
+
+impl Display for LoadbalanceEndpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<loadbalance")?;
+        if let Some(algorithm) = &self.algorithm {
+            write!(f, " algorithm=\"{}\"", escape_str_attribute(algorithm))?;
+        }
+        if let Some(failover) = &self.failover {
+            write!(f, " failover=\"{}\"", failover)?;
+        }
+        write!(f, ">")?;
+        for member in &self.members {
+            write!(f, "{}", member)?;
+        }
+        write!(f, "</loadbalance>")
+    }
+}
+
+impl Display for FailoverEndpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<failover")?;
+        if let Some(build_message) = &self.build_message {
+            write!(f, " buildMessage=\"{}\"", build_message)?;
+        }
+        write!(f, ">")?;
+        for member in &self.members {
+            write!(f, "{}", member)?;
+        }
+        write!(f, "</failover>")
+    }
+}
+
+impl Display for RecipientListEndpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<recipientlist>")?;
+        for member in &self.members {
+            write!(f, "{}", member)?;
+        }
+        write!(f, "</recipientlist>")
+    }
+}
+
+impl Display for Qos {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(timeout) = &self.timeout {
+            write!(f, "{}", timeout)?;
+        }
+        if let Some(suspend_on_failure) = &self.suspend_on_failure {
+            write!(f, "{}", suspend_on_failure)?;
+        }
+        if let Some(mark_for_suspension) = &self.mark_for_suspension {
+            write!(f, "{}", mark_for_suspension)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Timeout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<timeout>")?;
+        if let Some(duration) = &self.duration {
+            write!(f, "<duration>{}</duration>", duration)?;
+        }
+        if let Some(response_action) = &self.response_action {
+            write!(f, "<responseAction>{}</responseAction>", response_action)?;
+        }
+        write!(f, "</timeout>")
+    }
+}
+
+impl Display for ResponseAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResponseAction::Fault => write!(f, "fault"),
+            ResponseAction::Discard => write!(f, "discard"),
+            ResponseAction::Never => write!(f, "never"),
+        }
+    }
+}
+
+impl FromStr for ResponseAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fault" => Ok(ResponseAction::Fault),
+            "discard" => Ok(ResponseAction::Discard),
+            "never" => Ok(ResponseAction::Never),
+            _ => bail!("expected fault, discard or never, found {}", s),
+        }
+    }
+}
+
+impl Display for EndpointFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EndpointFormat::Soap11 => write!(f, "soap11"),
+            EndpointFormat::Soap12 => write!(f, "soap12"),
+            EndpointFormat::Pox => write!(f, "pox"),
+            EndpointFormat::Get => write!(f, "get"),
+            EndpointFormat::Rest => write!(f, "rest"),
+        }
+    }
+}
+
+impl FromStr for EndpointFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "soap11" => Ok(EndpointFormat::Soap11),
+            "soap12" => Ok(EndpointFormat::Soap12),
+            "pox" => Ok(EndpointFormat::Pox),
+            "get" => Ok(EndpointFormat::Get),
+            "rest" => Ok(EndpointFormat::Rest),
+            _ => bail!("expected soap11, soap12, pox, get or rest, found {}", s),
+        }
+    }
+}
+
+impl Display for Optimize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Optimize::Mtom => write!(f, "mtom"),
+            Optimize::Swa => write!(f, "swa"),
+        }
+    }
+}
+
+impl FromStr for Optimize {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mtom" => Ok(Optimize::Mtom),
+            "swa" => Ok(Optimize::Swa),
+            _ => bail!("expected mtom or swa, found {}", s),
+        }
+    }
+}
+
+impl Display for SuspendOnFailure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<suspendOnFailure>")?;
+        write_error_codes(f, &self.error_codes)?;
+        if let Some(initial_duration) = &self.initial_duration {
+            write!(f, "<initialDuration>{}</initialDuration>", initial_duration)?;
+        }
+        if let Some(progression_factor) = &self.progression_factor {
+            write!(
+                f,
+                "<progressionFactor>{:?}</progressionFactor>",
+                progression_factor
+            )?;
+        }
+        if let Some(maximum_duration) = &self.maximum_duration {
+            write!(f, "<maximumDuration>{}</maximumDuration>", maximum_duration)?;
+        }
+        write!(f, "</suspendOnFailure>")
+    }
+}
+
+impl Display for MarkForSuspension {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<markForSuspension>")?;
+        write_error_codes(f, &self.error_codes)?;
+        if let Some(retries) = &self.retries_before_suspension {
+            write!(
+                f,
+                "<retriesBeforeSuspension>{}</retriesBeforeSuspension>",
+                retries
+            )?;
+        }
+        if let Some(retry_delay) = &self.retry_delay {
+            write!(f, "<retryDelay>{}</retryDelay>", retry_delay)?;
+        }
+        write!(f, "</markForSuspension>")
+    }
+}
+
+fn write_format(
+    f: &mut Formatter<'_>,
+    format: &Option<EndpointFormat>,
+    optimize: &Option<Optimize>,
+) -> std::fmt::Result {
+    if let Some(format) = format {
+        write!(f, " format=\"{}\"", format)?;
+    }
+    if let Some(optimize) = optimize {
+        write!(f, " optimize=\"{}\"", optimize)?;
+    }
+    Ok(())
+}
+
+fn write_error_codes(f: &mut Formatter<'_>, error_codes: &[i32]) -> std::fmt::Result {
+    if error_codes.is_empty() {
+        return Ok(());
+    }
+    let error_codes: Vec<String> = error_codes.iter().map(|code| code.to_string()).collect();
+    write!(f, "<errorCodes>{}</errorCodes>", error_codes.join(","))
+}
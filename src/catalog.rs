@@ -0,0 +1,711 @@
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+//runtimes are ordered from oldest to newest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Runtime {
+    Ei660,
+    Mi410,
+    Mi440,
+}
+
+#[derive(Debug)]
+pub struct MediatorInfo {
+    pub name: &'static str,
+    pub required_attributes: &'static [&'static str],
+    pub optional_attributes: &'static [&'static str],
+    pub children: &'static [&'static str],
+    //oldest runtime providing the mediator
+    pub since: Runtime,
+    //oldest runtime which no longer supports the mediator, e.g. EI mediators dropped by MI
+    pub deprecated_since: Option<Runtime>,
+    pub doc_url: &'static str,
+}
+
+pub static MEDIATORS: &[MediatorInfo] = &[
+    MediatorInfo {
+        name: "log",
+        required_attributes: &[],
+        optional_attributes: &["level", "category", "separator", "description"],
+        children: &["property"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/log-mediator/",
+    },
+    MediatorInfo {
+        name: "property",
+        required_attributes: &["name"],
+        optional_attributes: &[
+            "value",
+            "expression",
+            "action",
+            "type",
+            "scope",
+            "pattern",
+            "group",
+            "description",
+        ],
+        children: &[],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/property-mediator/",
+    },
+    MediatorInfo {
+        name: "call",
+        required_attributes: &[],
+        optional_attributes: &["blocking", "description"],
+        children: &["endpoint"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/call-mediator/",
+    },
+    MediatorInfo {
+        name: "filter",
+        required_attributes: &[],
+        optional_attributes: &["xpath", "source", "regex", "description"],
+        children: &["then", "else"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/filter-mediator/",
+    },
+    MediatorInfo {
+        name: "switch",
+        required_attributes: &["source"],
+        optional_attributes: &["description"],
+        children: &["case", "default"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/switch-mediator/",
+    },
+    MediatorInfo {
+        name: "payloadFactory",
+        required_attributes: &[],
+        optional_attributes: &["media-type", "template-type", "description"],
+        children: &["format", "args"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/payloadfactory-mediator/",
+    },
+    MediatorInfo {
+        name: "header",
+        required_attributes: &[],
+        optional_attributes: &[
+            "name",
+            "value",
+            "expression",
+            "action",
+            "scope",
+            "description",
+        ],
+        children: &[],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/header-mediator/",
+    },
+    MediatorInfo {
+        name: "class",
+        required_attributes: &["name"],
+        optional_attributes: &["description"],
+        children: &["property"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/class-mediator/",
+    },
+    MediatorInfo {
+        name: "sequence",
+        required_attributes: &["key"],
+        optional_attributes: &["description"],
+        children: &[],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/sequence-mediator/",
+    },
+    MediatorInfo {
+        name: "enrich",
+        required_attributes: &[],
+        optional_attributes: &["description"],
+        children: &["source", "target"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/enrich-mediator/",
+    },
+    MediatorInfo {
+        name: "xslt",
+        required_attributes: &["key"],
+        optional_attributes: &["source", "description"],
+        children: &["property", "feature", "resource"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/xslt-mediator/",
+    },
+    MediatorInfo {
+        name: "xquery",
+        required_attributes: &["key"],
+        optional_attributes: &["target", "description"],
+        children: &["variable"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/xquery-mediator/",
+    },
+    MediatorInfo {
+        name: "validate",
+        required_attributes: &[],
+        optional_attributes: &["source", "cache-schema", "description"],
+        children: &["schema", "resource", "feature", "property", "on-fail"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/validate-mediator/",
+    },
+    MediatorInfo {
+        name: "script",
+        required_attributes: &["language"],
+        optional_attributes: &["key", "function", "description"],
+        children: &["include"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/script-mediator/",
+    },
+    MediatorInfo {
+        name: "iterate",
+        required_attributes: &["expression"],
+        optional_attributes: &[
+            "id",
+            "attachPath",
+            "preservePayload",
+            "sequential",
+            "continueParent",
+            "description",
+        ],
+        children: &["target"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/iterate-mediator/",
+    },
+    MediatorInfo {
+        name: "aggregate",
+        required_attributes: &[],
+        optional_attributes: &["id", "description"],
+        children: &["correlateOn", "completeCondition", "onComplete"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/aggregate-mediator/",
+    },
+    MediatorInfo {
+        name: "clone",
+        required_attributes: &[],
+        optional_attributes: &["id", "continueParent", "sequential", "description"],
+        children: &["target"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/clone-mediator/",
+    },
+    MediatorInfo {
+        name: "send",
+        required_attributes: &[],
+        optional_attributes: &["receive", "buildmessage", "description"],
+        children: &["endpoint"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/send-mediator/",
+    },
+    MediatorInfo {
+        name: "callout",
+        required_attributes: &[],
+        optional_attributes: &[
+            "serviceURL",
+            "endpointKey",
+            "action",
+            "initAxis2ClientOptions",
+            "description",
+        ],
+        children: &["configuration", "source", "target", "enableSec"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/callout-mediator/",
+    },
+    MediatorInfo {
+        name: "store",
+        required_attributes: &["messageStore"],
+        optional_attributes: &["sequence", "description"],
+        children: &[],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/store-mediator/",
+    },
+    MediatorInfo {
+        name: "makefault",
+        required_attributes: &[],
+        optional_attributes: &["version", "response", "description"],
+        children: &["code", "reason", "node", "role", "detail"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/fault-mediator/",
+    },
+    MediatorInfo {
+        name: "cache",
+        required_attributes: &[],
+        optional_attributes: &[
+            "id",
+            "collector",
+            "timeout",
+            "maxMessageSize",
+            "description",
+        ],
+        children: &["onCacheHit", "protocol", "implementation"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/cache-mediator/",
+    },
+    MediatorInfo {
+        name: "respond",
+        required_attributes: &[],
+        optional_attributes: &["description"],
+        children: &[],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/respond-mediator/",
+    },
+    MediatorInfo {
+        name: "drop",
+        required_attributes: &[],
+        optional_attributes: &["description"],
+        children: &[],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/drop-mediator/",
+    },
+    MediatorInfo {
+        name: "loopback",
+        required_attributes: &[],
+        optional_attributes: &["description"],
+        children: &[],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/loopback-mediator/",
+    },
+    MediatorInfo {
+        name: "variable",
+        required_attributes: &["name"],
+        optional_attributes: &["value", "expression", "type", "action", "description"],
+        children: &[],
+        since: Runtime::Mi440,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/variable-mediator/",
+    },
+    MediatorInfo {
+        name: "scatter-gather",
+        required_attributes: &[],
+        optional_attributes: &[
+            "parallel-execution",
+            "target",
+            "target-variable",
+            "result-content-type",
+            "result-enclose-element-name",
+            "description",
+        ],
+        children: &["aggregation", "sequence"],
+        since: Runtime::Mi440,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/scatter-gather-mediator/",
+    },
+    MediatorInfo {
+        name: "enqueue",
+        required_attributes: &["executor", "sequence"],
+        optional_attributes: &["priority", "description"],
+        children: &[],
+        since: Runtime::Ei660,
+        deprecated_since: Some(Runtime::Mi410),
+        doc_url: "https://docs.wso2.com/display/EI660/Enqueue+Mediator",
+    },
+    MediatorInfo {
+        name: "event",
+        required_attributes: &[],
+        optional_attributes: &["topic", "expression", "description"],
+        children: &[],
+        since: Runtime::Ei660,
+        deprecated_since: Some(Runtime::Mi410),
+        doc_url: "https://docs.wso2.com/display/EI660/Event+Mediator",
+    },
+    MediatorInfo {
+        name: "spring",
+        required_attributes: &["bean", "key"],
+        optional_attributes: &["description"],
+        children: &[],
+        since: Runtime::Ei660,
+        deprecated_since: Some(Runtime::Mi410),
+        doc_url: "https://docs.wso2.com/display/EI660/Spring+Mediator",
+    },
+    MediatorInfo {
+        name: "router",
+        required_attributes: &[],
+        optional_attributes: &["continueAfter", "description"],
+        children: &["route"],
+        since: Runtime::Ei660,
+        deprecated_since: Some(Runtime::Mi410),
+        doc_url: "https://docs.wso2.com/display/EI660/Router+Mediator",
+    },
+    MediatorInfo {
+        name: "conditionalRouter",
+        required_attributes: &[],
+        optional_attributes: &["continueAfter", "description"],
+        children: &["conditionalRoute"],
+        since: Runtime::Ei660,
+        deprecated_since: Some(Runtime::Mi410),
+        doc_url: "https://docs.wso2.com/display/EI660/Conditional+Router+Mediator",
+    },
+    MediatorInfo {
+        name: "dblookup",
+        required_attributes: &[],
+        optional_attributes: &["description"],
+        children: &["connection", "statement"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/dblookup-mediator/",
+    },
+    MediatorInfo {
+        name: "dbreport",
+        required_attributes: &[],
+        optional_attributes: &["useTransaction", "description"],
+        children: &["connection", "statement"],
+        since: Runtime::Ei660,
+        deprecated_since: None,
+        doc_url: "https://mi.docs.wso2.com/en/latest/reference/mediators/dbreport-mediator/",
+    },
+];
+
+//properties set by the runtime itself in the synapse ($ctx) scope
+pub static SYNAPSE_PROPERTIES: &[&str] = &[
+    "ERROR_CODE",
+    "ERROR_MESSAGE",
+    "ERROR_DETAIL",
+    "ERROR_EXCEPTION",
+    "RESPONSE",
+    "OUT_ONLY",
+    "REST_API_CONTEXT",
+    "REST_FULL_REQUEST_PATH",
+    "REST_SUB_REQUEST_PATH",
+    "REST_METHOD",
+    "SYNAPSE_REST_API",
+    "SYNAPSE_REST_API_VERSION",
+    "TRANSPORT_IN_NAME",
+    "To",
+    "From",
+    "Action",
+    "FaultTo",
+    "ReplyTo",
+    "MessageID",
+    "FAULT",
+    "MESSAGE_FORMAT",
+    "OperationName",
+    "SYSTEM_DATE",
+    "SYSTEM_TIME",
+];
+
+//properties set by the runtime itself in the axis2 ($axis2) scope
+pub static AXIS2_PROPERTIES: &[&str] = &[
+    "HTTP_SC",
+    "HTTP_SC_DESC",
+    "HTTP_METHOD",
+    "REST_URL_POSTFIX",
+    "ContentType",
+    "messageType",
+    "TRANSPORT_HEADERS",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgType {
+    String,
+    Number,
+    Boolean,
+    NodeSet,
+    Any,
+}
+
+//signature of an xpath function, the last min_args.. arguments are optional and the last
+//argument of a variadic function may be repeated, argument types are not checked as expressions
+//are not typed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FunctionInfo<'a> {
+    pub name: &'a str,
+    pub args: &'a [ArgType],
+    pub min_args: usize,
+    pub variadic: bool,
+}
+
+//xpath 1.0 core functions and the synapse extension functions
+pub static FUNCTIONS: &[FunctionInfo<'static>] = &[
+    FunctionInfo {
+        name: "last",
+        args: &[],
+        min_args: 0,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "position",
+        args: &[],
+        min_args: 0,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "count",
+        args: &[ArgType::NodeSet],
+        min_args: 1,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "id",
+        args: &[ArgType::Any],
+        min_args: 1,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "local-name",
+        args: &[ArgType::NodeSet],
+        min_args: 0,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "namespace-uri",
+        args: &[ArgType::NodeSet],
+        min_args: 0,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "name",
+        args: &[ArgType::NodeSet],
+        min_args: 0,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "string",
+        args: &[ArgType::Any],
+        min_args: 0,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "concat",
+        args: &[ArgType::String, ArgType::String],
+        min_args: 2,
+        variadic: true,
+    },
+    FunctionInfo {
+        name: "starts-with",
+        args: &[ArgType::String, ArgType::String],
+        min_args: 2,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "contains",
+        args: &[ArgType::String, ArgType::String],
+        min_args: 2,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "substring-before",
+        args: &[ArgType::String, ArgType::String],
+        min_args: 2,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "substring-after",
+        args: &[ArgType::String, ArgType::String],
+        min_args: 2,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "substring",
+        args: &[ArgType::String, ArgType::Number, ArgType::Number],
+        min_args: 2,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "string-length",
+        args: &[ArgType::String],
+        min_args: 0,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "normalize-space",
+        args: &[ArgType::String],
+        min_args: 0,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "translate",
+        args: &[ArgType::String, ArgType::String, ArgType::String],
+        min_args: 3,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "boolean",
+        args: &[ArgType::Any],
+        min_args: 1,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "not",
+        args: &[ArgType::Boolean],
+        min_args: 1,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "true",
+        args: &[],
+        min_args: 0,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "false",
+        args: &[],
+        min_args: 0,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "lang",
+        args: &[ArgType::String],
+        min_args: 1,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "number",
+        args: &[ArgType::Any],
+        min_args: 0,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "sum",
+        args: &[ArgType::NodeSet],
+        min_args: 1,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "floor",
+        args: &[ArgType::Number],
+        min_args: 1,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "ceiling",
+        args: &[ArgType::Number],
+        min_args: 1,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "round",
+        args: &[ArgType::Number],
+        min_args: 1,
+        variadic: false,
+    },
+    //get-property('[scope',] 'NAME'[, 'DATE_PATTERN'])
+    FunctionInfo {
+        name: "get-property",
+        args: &[ArgType::String, ArgType::String, ArgType::String],
+        min_args: 1,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "json-eval",
+        args: &[ArgType::String],
+        min_args: 1,
+        variadic: false,
+    },
+    //the optional argument is the charset
+    FunctionInfo {
+        name: "base64Encode",
+        args: &[ArgType::String, ArgType::String],
+        min_args: 1,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "base64Decode",
+        args: &[ArgType::String, ArgType::String],
+        min_args: 1,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "url-encode",
+        args: &[ArgType::String, ArgType::String],
+        min_args: 1,
+        variadic: false,
+    },
+    FunctionInfo {
+        name: "url-decode",
+        args: &[ArgType::String],
+        min_args: 1,
+        variadic: false,
+    },
+];
+
+pub fn mediator(name: &str) -> Option<&'static MediatorInfo> {
+    MEDIATORS.iter().find(|info| info.name == name)
+}
+
+pub fn function(name: &str) -> Option<&'static FunctionInfo<'static>> {
+    FUNCTIONS.iter().find(|info| info.name == name)
+}
+
+//--------------------------------------------------------------------------------//
+impl MediatorInfo {
+    pub fn allows_attribute(&self, attribute: &str) -> bool {
+        self.required_attributes.contains(&attribute)
+            || self.optional_attributes.contains(&attribute)
+    }
+
+    pub fn allows_child(&self, child: &str) -> bool {
+        self.children.contains(&child)
+    }
+}
+
+impl FunctionInfo<'_> {
+    pub fn accepts(&self, args: usize) -> bool {
+        args >= self.min_args && (self.variadic || args <= self.args.len())
+    }
+}
+
+impl Display for Runtime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Runtime::Ei660 => write!(f, "EI 6.6.0"),
+            Runtime::Mi410 => write!(f, "MI 4.1.0"),
+            Runtime::Mi440 => write!(f, "MI 4.4.0"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::catalog::{self, Runtime};
+
+    #[test]
+    fn test_mediator_lookup() {
+        let log = catalog::mediator("log").unwrap();
+        assert!(log.allows_attribute("level"));
+        assert!(log.allows_child("property"));
+        assert!(!log.allows_child("log"));
+
+        let property = catalog::mediator("property").unwrap();
+        assert_eq!(property.required_attributes, &["name"]);
+
+        assert!(catalog::mediator("foo").is_none());
+
+        let variable = catalog::mediator("variable").unwrap();
+        assert_eq!(variable.since, Runtime::Mi440);
+        assert!(variable.since > catalog::mediator("log").unwrap().since);
+        assert_eq!(variable.deprecated_since, None);
+
+        let spring = catalog::mediator("spring").unwrap();
+        assert_eq!(spring.deprecated_since, Some(Runtime::Mi410));
+    }
+
+    #[test]
+    fn test_function_lookup() {
+        let substring = catalog::function("substring").unwrap();
+        assert!(!substring.accepts(1));
+        assert!(substring.accepts(2));
+        assert!(substring.accepts(3));
+        assert!(!substring.accepts(4));
+
+        assert!(catalog::function("concat").unwrap().accepts(5));
+        assert!(catalog::function("fn:concat").is_none());
+    }
+}
@@ -0,0 +1,169 @@
+use crate::ast;
+
+pub const SYNAPSE_NAMESPACE: &str = "http://ws.apache.org/ns/synapse";
+
+//proxy forwarding every message to the endpoint and the response back to the client
+pub fn pass_through_proxy(name: &str, endpoint: ast::EndpointRef) -> ast::Proxy {
+    ast::Proxy {
+        name: name.to_string(),
+        xmlns: Some(SYNAPSE_NAMESPACE.to_string()),
+        transports: vec!["http".to_string(), "https".to_string()],
+        start_on_load: Some(true),
+        trace: None,
+        statistics: None,
+        description: None,
+        target: Some(ast::ProxyTarget {
+            in_sequence: None,
+            out_sequence: None,
+            fault_sequence: None,
+            endpoint: Some(endpoint),
+            sequences: Vec::new(),
+        }),
+        publish_wsdl: None,
+        parameters: Vec::new(),
+    }
+}
+
+//rest api with list, read, create, update and delete resources calling the named endpoint,
+//the id is available to the endpoint as uri.var.id
+pub fn crud_api(name: &str, context: &str, endpoint_key: &str) -> ast::Api {
+    let resources = [
+        (ast::HttpMethod::Get, "/"),
+        (ast::HttpMethod::Get, "/{id}"),
+        (ast::HttpMethod::Post, "/"),
+        (ast::HttpMethod::Put, "/{id}"),
+        (ast::HttpMethod::Delete, "/{id}"),
+    ]
+    .into_iter()
+    .map(|(method, uri_template)| ast::Resource {
+        methods: vec![method],
+        uri_template: Some(uri_template.to_string()),
+        url_mapping: None,
+        sequences: vec![ast::Sequences::InSequence(ast::InSequence {
+            mediators: vec![
+                ast::Mediators::Call(ast::CallMediator {
+                    blocking: None,
+                    endpoint: Some(ast::EndpointRef::Key(endpoint_key.to_string())),
+                }),
+                ast::Mediators::Respond,
+            ],
+        })],
+    })
+    .collect();
+
+    ast::Api {
+        context: context.to_string(),
+        name: name.to_string(),
+        xmlns: Some(SYNAPSE_NAMESPACE.to_string()),
+        trace: None,
+        statistics: None,
+        version: None,
+        version_type: None,
+        resources,
+        handlers: Vec::new(),
+    }
+}
+
+//sequence storing every message in an in-memory message store, the message processor forwards
+//the stored messages to the named endpoint. the client gets 202 accepted without waiting for it
+pub fn store_and_forward(
+    name: &str,
+    endpoint_key: &str,
+) -> (ast::NamedSequence, ast::MessageStore, ast::MessageProcessor) {
+    let store = format!("{}Store", name);
+
+    let property = |name: &str, scope| {
+        ast::Mediators::Property(ast::PropertyMediator {
+            name: name.to_string(),
+            action: None,
+            value: "true".to_string(),
+            expression: None,
+            scope,
+            kind: None,
+            pattern: None,
+            group: None,
+            inline: Vec::new(),
+        })
+    };
+    let sequence = ast::NamedSequence {
+        name: name.to_string(),
+        xmlns: Some(SYNAPSE_NAMESPACE.to_string()),
+        on_error: None,
+        trace: None,
+        statistics: None,
+        mediators: vec![
+            property("FORCE_SC_ACCEPTED", Some(ast::Scope::Axis2)),
+            property("OUT_ONLY", None),
+            ast::Mediators::Store(ast::StoreMediator {
+                message_store: store.clone(),
+                sequence: None,
+            }),
+        ],
+    };
+
+    let message_store = ast::MessageStore {
+        name: store.clone(),
+        xmlns: Some(SYNAPSE_NAMESPACE.to_string()),
+        class: Some("org.apache.synapse.message.store.impl.memory.InMemoryStore".to_string()),
+        parameters: Vec::new(),
+    };
+
+    let message_processor = ast::MessageProcessor {
+        name: format!("{}Processor", name),
+        xmlns: Some(SYNAPSE_NAMESPACE.to_string()),
+        class: "org.apache.synapse.message.processor.impl.forwarder.ScheduledMessageForwardingProcessor"
+            .to_string(),
+        message_store: store,
+        target_endpoint: Some(endpoint_key.to_string()),
+        parameters: [("interval", "1000"), ("max.delivery.attempts", "4")]
+            .into_iter()
+            .map(|(name, value)| ast::Parameter {
+                name: name.to_string(),
+                value: value.to_string(),
+            })
+            .collect(),
+    };
+
+    (sequence, message_store, message_processor)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ast, codegen, Parser};
+
+    #[test]
+    fn test_generators() {
+        let (sequence, message_store, message_processor) =
+            codegen::store_and_forward("Orders", "orders");
+        assert_eq!(message_store.name, "OrdersStore");
+        assert_eq!(message_processor.message_store, "OrdersStore");
+        assert_eq!(message_processor.target_endpoint.as_deref(), Some("orders"));
+
+        let program = ast::Program {
+            ast_nodes: vec![
+                ast::AstNode::Proxy(Box::new(codegen::pass_through_proxy(
+                    "OrdersProxy",
+                    ast::EndpointRef::Key("orders".to_string()),
+                ))),
+                ast::AstNode::Api(codegen::crud_api("OrdersAPI", "/orders", "orders")),
+                ast::AstNode::Sequence(ast::Sequences::NamedSequence(sequence)),
+                ast::AstNode::MessageStore(message_store),
+                ast::AstNode::MessageProcessor(message_processor),
+            ],
+            format: ast::SourceFormat::default(),
+        };
+
+        assert_eq!(program.mediators().len(), 13);
+        assert!(matches!(
+            program.mediators()[12],
+            ast::Mediators::Store(store) if store.message_store == "OrdersStore"
+        ));
+
+        //generated artifacts serialize to xml the parser accepts
+        for ast_node in &program.ast_nodes {
+            let output = ast_node.to_string();
+            let reparsed = Parser::new(output.as_bytes()).parse_progarm().unwrap();
+            assert_eq!(reparsed.to_string(), output);
+        }
+    }
+}
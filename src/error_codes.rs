@@ -0,0 +1,93 @@
+use anyhow::Result;
+use anyhow::{bail, Context};
+
+//error codes used by suspendOnFailure and markForSuspension, -1 disables the setting
+pub const DISABLED: i32 = -1;
+
+static ERROR_CODES: &[(i32, &str)] = &[
+    (DISABLED, "disabled"),
+    (101000, "receiver IO error sending"),
+    (101001, "receiver IO error receiving"),
+    (101500, "sender IO error sending"),
+    (101501, "sender IO error receiving"),
+    (101503, "connection failed"),
+    (101504, "connection timed out"),
+    (101505, "connection closed"),
+    (101506, "protocol violation"),
+    (101507, "connect cancel"),
+    (101508, "connect timeout"),
+    (101509, "send abort"),
+    (101510, "response processing failure"),
+    (303000, "endpoint not ready"),
+    (303001, "address endpoint not ready"),
+    (303002, "wsdl endpoint not ready"),
+    (303100, "failover in loadbalance endpoint"),
+    (304100, "failover in failover endpoint"),
+    (
+        309001,
+        "session aware loadbalance endpoint has no ready child endpoints",
+    ),
+    (
+        309002,
+        "session aware loadbalance endpoint has an invalid reference",
+    ),
+    (309003, "session aware loadbalance endpoint session failed"),
+    (500000, "endpoint custom error"),
+];
+
+//parse a comma separated list of error codes, e.g. the content of <errorCodes>
+pub fn parse_error_codes(text: &str) -> Result<Vec<i32>> {
+    let mut codes = Vec::new();
+
+    for code in text.split(',').map(str::trim) {
+        let code: i32 = code
+            .parse()
+            .with_context(|| format!("invalid error code: {}", code))?;
+
+        if !is_valid(code) {
+            bail!(
+                "error code {} is outside of the known error code ranges",
+                code
+            );
+        }
+        codes.push(code);
+    }
+
+    Result::Ok(codes)
+}
+
+//transport (1xxxxx), endpoint (3xxxxx), mediator (4xxxxx) and custom (5xxxxx) error codes
+pub fn is_valid(code: i32) -> bool {
+    code == DISABLED || (100000..200000).contains(&code) || (300000..600000).contains(&code)
+}
+
+pub fn error_code_name(code: i32) -> Option<&'static str> {
+    ERROR_CODES
+        .iter()
+        .find(|(known, _)| *known == code)
+        .map(|(_, name)| *name)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error_codes;
+
+    #[test]
+    fn test_parse_error_codes() {
+        assert_eq!(error_codes::parse_error_codes("-1").unwrap(), vec![-1]);
+        assert_eq!(
+            error_codes::parse_error_codes("101504, 101505").unwrap(),
+            vec![101504, 101505]
+        );
+
+        assert!(error_codes::parse_error_codes("").is_err());
+        assert!(error_codes::parse_error_codes("101504,foo").is_err());
+        assert!(error_codes::parse_error_codes("42").is_err());
+
+        assert_eq!(
+            error_codes::error_code_name(101504),
+            Some("connection timed out")
+        );
+        assert_eq!(error_codes::error_code_name(101599), None);
+    }
+}
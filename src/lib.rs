@@ -1,301 +1,4453 @@
 use anyhow::Result;
 use anyhow::{bail, Context};
-use std::io::BufRead;
+use std::io::{self, BufRead, Read};
 
 use xml::{
-    name::OwnedName,
+    attribute::OwnedAttribute,
+    common::Position,
+    namespace::Namespace,
     reader::{EventReader, ParserConfig, XmlEvent},
 };
 
+pub mod analysis;
 pub mod ast;
+use ast::endpoint;
+pub mod catalog;
+pub mod codegen;
+pub mod error_codes;
+pub mod refactor;
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
+pub mod validate;
 
-struct Parser<R: BufRead> {
-    event_reader: EventReader<R>,
+pub enum FragmentContext {
+    Mediator,
+    Mediators,
+    Endpoint,
+}
+
+#[derive(Debug)]
+pub enum Fragment {
+    Mediator(ast::Mediators),
+    Mediators(Vec<ast::Mediators>),
+    Endpoint(ast::EndpointRef),
+}
+
+//parse a snippet of a document (e.g. the part an editor is working on) in the given context
+pub fn parse_fragment(text: &str, context: FragmentContext) -> Result<Fragment> {
+    //wrap the snippet so that a list of mediators has a single root element
+    let input = format!("<fragment>{}</fragment>", text);
+    let mut parser = Parser::new(input.as_bytes());
+    parser.parse_fragment(context)
+}
+
+//parse a stream of concatenated documents, each starting with its own xml declaration, the
+//documents may use any ascii compatible encoding (e.g. utf-8 or iso-8859-1)
+pub fn parse_documents(input: &[u8]) -> Result<Vec<ast::Program>> {
+    let starts = document_starts(input);
+
+    let mut programs = Vec::new();
+    for (i, start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).copied().unwrap_or(input.len());
+        let document = &input[*start..end];
+        if document.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+
+        let mut parser = Parser::new(document);
+        let program = parser
+            .parse_progarm()
+            .with_context(|| format!("error parsing document {}", programs.len() + 1))?;
+        programs.push(program);
+    }
+
+    Result::Ok(programs)
+}
+
+//offsets of the documents of a stream, an xml declaration starts a new document only after the
+//root element of the previous document is closed, declarations in comments, cdata sections and
+//processing instructions are skipped
+fn document_starts(input: &[u8]) -> Vec<usize> {
+    let find = |from: usize, pattern: &[u8]| {
+        input[from..]
+            .windows(pattern.len())
+            .position(|window| window == pattern)
+            .map_or(input.len(), |position| from + position + pattern.len())
+    };
+
+    let mut starts = vec![0];
+    let mut depth = 0usize;
+    let mut root_closed = false;
+    let mut index = 0;
+
+    while index < input.len() {
+        let rest = &input[index..];
+        index = if rest.starts_with(b"<!--") {
+            find(index, b"-->")
+        } else if rest.starts_with(b"<![CDATA[") {
+            find(index, b"]]>")
+        } else if rest.starts_with(b"<?") {
+            let declaration = rest.starts_with(b"<?xml")
+                && rest
+                    .get(5)
+                    .is_some_and(|c| c.is_ascii_whitespace() || *c == b'?');
+            if declaration && depth == 0 && root_closed {
+                starts.push(index);
+                root_closed = false;
+            }
+            find(index, b"?>")
+        } else if rest.starts_with(b"<!") {
+            find(index, b">")
+        } else if rest.starts_with(b"</") {
+            depth = depth.saturating_sub(1);
+            root_closed |= depth == 0;
+            find(index, b">")
+        } else if rest.starts_with(b"<") {
+            //attribute values may contain >
+            let mut quote = None;
+            let end = rest
+                .iter()
+                .position(|c| match (quote, *c) {
+                    (Some(open), c) if c == open => {
+                        quote = None;
+                        false
+                    }
+                    (Some(_), _) => false,
+                    (None, b'"' | b'\'') => {
+                        quote = Some(*c);
+                        false
+                    }
+                    (None, c) => c == b'>',
+                })
+                .unwrap_or(rest.len() - 1);
+            if rest[end.saturating_sub(1)] == b'/' {
+                root_closed |= depth == 0;
+            } else {
+                depth += 1;
+            }
+            index + end + 1
+        } else {
+            index + 1
+        };
+    }
+
+    starts
+}
+
+//attribute lookup shared by start elements and raw elements
+trait Attributes {
+    fn attribute(&self, name: &str) -> Option<&str>;
+}
+
+//attributes of a start element are looked up by their local name
+impl Attributes for Vec<OwnedAttribute> {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.iter()
+            .find(|attr| attr.name.local_name == name)
+            .map(|attr| attr.value.as_str())
+    }
+}
+
+impl Attributes for ast::RawElement {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        ast::RawElement::attribute(self, name)
+    }
+}
+
+//owned value of the attribute
+fn attribute(attributes: &impl Attributes, name: &str) -> Option<String> {
+    attributes.attribute(name).map(String::from)
+}
+
+//typed value of an optional attribute, invalid values are reported with the attribute name
+fn parse_attribute<T>(attributes: &impl Attributes, name: &str) -> Result<Option<T>>
+where
+    T: std::str::FromStr,
+    T::Err: Into<anyhow::Error>,
+{
+    attributes
+        .attribute(name)
+        .map(|value| value.parse::<T>().map_err(Into::into))
+        .transpose()
+        .with_context(|| format!("invalid value for attribute {}", name))
+}
+
+//typed text content of an element like <duration>15000</duration>
+fn parse_text<T>(element: &ast::RawElement) -> Result<T>
+where
+    T: std::str::FromStr,
+    T::Err: Into<anyhow::Error>,
+{
+    element
+        .text()
+        .trim()
+        .parse::<T>()
+        .map_err(Into::into)
+        .with_context(|| format!("invalid value for element {}", element.name))
+}
+
+//--------------------------------------------------------------------------------//
+//endpoints are captured as raw elements first and then converted into their typed form
+
+fn parse_endpoint_ref(element: &ast::RawElement) -> Result<ast::EndpointRef> {
+    match element.attribute("key") {
+        Some(key) if element.children.is_empty() => {
+            Result::Ok(ast::EndpointRef::Key(key.to_string()))
+        }
+        _ => Result::Ok(ast::EndpointRef::Inline(Box::new(
+            parse_endpoint(element).context("error parsing endpoint")?,
+        ))),
+    }
+}
+
+fn parse_endpoint(element: &ast::RawElement) -> Result<endpoint::Endpoint> {
+    let mut kind = None;
+    let mut properties = Vec::new();
+    let mut description = None;
+
+    for child in element.elements() {
+        match child.name.as_str() {
+            "property" => {
+                properties.push(parse_raw_property(child)?);
+                continue;
+            }
+            "description" => {
+                description = Some(child.text());
+                continue;
+            }
+            _ => {}
+        }
+        if kind.is_some() {
+            bail!(
+                "endpoint with more than one definition: element {}",
+                child.name
+            );
+        }
+        kind = Some(match child.name.as_str() {
+            "http" => endpoint::EndpointKind::Http(parse_http_endpoint(child)?),
+            "address" => endpoint::EndpointKind::Address(endpoint::AddressEndpoint {
+                uri: child
+                    .attribute("uri")
+                    .context("address endpoint without uri")?
+                    .to_string(),
+                format: parse_attribute(child, "format")?,
+                optimize: parse_attribute(child, "optimize")?,
+                qos: parse_leaf_qos(child)?,
+            }),
+            "wsdl" => endpoint::EndpointKind::Wsdl(endpoint::WsdlEndpoint {
+                uri: child
+                    .attribute("uri")
+                    .context("wsdl endpoint without uri")?
+                    .to_string(),
+                service: child.attribute("service").map(String::from),
+                port: child.attribute("port").map(String::from),
+                qos: parse_leaf_qos(child)?,
+            }),
+            "default" => endpoint::EndpointKind::Default(endpoint::DefaultEndpoint {
+                format: parse_attribute(child, "format")?,
+                optimize: parse_attribute(child, "optimize")?,
+                qos: parse_leaf_qos(child)?,
+            }),
+            "loadbalance" => endpoint::EndpointKind::Loadbalance(endpoint::LoadbalanceEndpoint {
+                algorithm: child.attribute("algorithm").map(String::from),
+                failover: parse_attribute(child, "failover")?,
+                members: parse_members(child)?,
+            }),
+            "failover" => endpoint::EndpointKind::Failover(endpoint::FailoverEndpoint {
+                build_message: parse_attribute(child, "buildMessage")?,
+                members: parse_members(child)?,
+            }),
+            "recipientlist" => {
+                endpoint::EndpointKind::RecipientList(endpoint::RecipientListEndpoint {
+                    members: parse_members(child)?,
+                })
+            }
+            _ => {
+                bail!("not a supported endpoint element: element {}", child.name);
+            }
+        });
+    }
+
+    Result::Ok(endpoint::Endpoint {
+        name: element.attribute("name").map(String::from),
+        xmlns: None,
+        kind: kind.context("endpoint without definition")?,
+        properties,
+        description,
+    })
+}
+
+fn parse_http_endpoint(element: &ast::RawElement) -> Result<endpoint::HttpEndpoint> {
+    Result::Ok(endpoint::HttpEndpoint {
+        method: parse_attribute(element, "method")?,
+        uri_template: element
+            .attribute("uri-template")
+            .context("http endpoint without uri-template")?
+            .to_string(),
+        qos: parse_leaf_qos(element)?,
+    })
+}
+
+//member endpoints of loadbalance, failover and recipientlist endpoints
+fn parse_members(element: &ast::RawElement) -> Result<Vec<ast::EndpointRef>> {
+    element
+        .elements()
+        .map(|child| match child.name.as_str() {
+            "endpoint" => parse_endpoint_ref(child),
+            _ => bail!(
+                "not a supported {} element: element {}",
+                element.name,
+                child.name
+            ),
+        })
+        .collect()
+}
+
+//qos settings of leaf endpoints (http, address, wsdl and default)
+fn parse_leaf_qos(element: &ast::RawElement) -> Result<endpoint::Qos> {
+    let mut qos = endpoint::Qos::default();
+    for child in element.elements() {
+        if !parse_qos(&mut qos, child)? {
+            bail!(
+                "not a supported {} endpoint element: element {}",
+                element.name,
+                child.name
+            );
+        }
+    }
+    Result::Ok(qos)
+}
+
+//returns false if the element is not a qos element
+fn parse_qos(qos: &mut endpoint::Qos, element: &ast::RawElement) -> Result<bool> {
+    match element.name.as_str() {
+        "timeout" => {
+            let mut timeout = endpoint::Timeout::default();
+            for child in element.elements() {
+                match child.name.as_str() {
+                    "duration" => timeout.duration = Some(parse_text(child)?),
+                    "responseAction" => timeout.response_action = Some(parse_text(child)?),
+                    _ => {
+                        bail!("not a supported timeout element: element {}", child.name);
+                    }
+                }
+            }
+            qos.timeout = Some(timeout);
+        }
+        "suspendOnFailure" => {
+            let mut suspend_on_failure = endpoint::SuspendOnFailure::default();
+            for child in element.elements() {
+                match child.name.as_str() {
+                    "errorCodes" => {
+                        suspend_on_failure.error_codes =
+                            error_codes::parse_error_codes(&child.text())?
+                    }
+                    "initialDuration" => {
+                        suspend_on_failure.initial_duration = Some(parse_text(child)?)
+                    }
+                    "progressionFactor" => {
+                        let factor: f64 = parse_text(child)?;
+                        //a factor below 1 would shorten the suspension after every failure
+                        if factor.is_nan() || factor < 1.0 {
+                            bail!("progressionFactor must be at least 1.0: {}", factor);
+                        }
+                        suspend_on_failure.progression_factor = Some(factor)
+                    }
+                    "maximumDuration" => {
+                        suspend_on_failure.maximum_duration = Some(parse_text(child)?)
+                    }
+                    _ => {
+                        bail!(
+                            "not a supported suspendOnFailure element: element {}",
+                            child.name
+                        );
+                    }
+                }
+            }
+            qos.suspend_on_failure = Some(suspend_on_failure);
+        }
+        "markForSuspension" => {
+            let mut mark_for_suspension = endpoint::MarkForSuspension::default();
+            for child in element.elements() {
+                match child.name.as_str() {
+                    "errorCodes" => {
+                        mark_for_suspension.error_codes =
+                            error_codes::parse_error_codes(&child.text())?
+                    }
+                    "retriesBeforeSuspension" => {
+                        mark_for_suspension.retries_before_suspension = Some(parse_text(child)?)
+                    }
+                    "retryDelay" => mark_for_suspension.retry_delay = Some(parse_text(child)?),
+                    _ => {
+                        bail!(
+                            "not a supported markForSuspension element: element {}",
+                            child.name
+                        );
+                    }
+                }
+            }
+            qos.mark_for_suspension = Some(mark_for_suspension);
+        }
+        _ => return Result::Ok(false),
+    }
+
+    Result::Ok(true)
+}
+
+//--------------------------------------------------------------------------------//
+//children of mediators and artifacts captured as raw elements
+
+fn parse_payload_arg(element: &ast::RawElement) -> Result<ast::PayloadArg> {
+    match (element.attribute("value"), element.attribute("expression")) {
+        (Some(value), None) => Result::Ok(ast::PayloadArg::Value(value.to_string())),
+        (None, Some(expression)) => Result::Ok(ast::PayloadArg::Expression {
+            expression: expression.to_string(),
+            evaluator: element.attribute("evaluator").map(String::from),
+            literal: parse_attribute(element, "literal")?,
+        }),
+        _ => bail!("arg needs either value or expression"),
+    }
+}
+
+fn parse_cache_protocol(element: &ast::RawElement) -> Result<ast::CacheProtocol> {
+    let mut protocol = ast::CacheProtocol {
+        kind: element
+            .attribute("type")
+            .context("cache protocol without type")?
+            .to_string(),
+        methods: None,
+        headers_to_exclude_in_hash: None,
+        headers_to_include_in_hash: None,
+        response_codes: None,
+        enable_cache_control: None,
+        include_age_header: None,
+        hash_generator: None,
+    };
+
+    for child in element.elements() {
+        let value = child.text();
+        match child.name.as_str() {
+            "methods" => protocol.methods = Some(value),
+            "headersToExcludeInHash" => protocol.headers_to_exclude_in_hash = Some(value),
+            "headersToIncludeInHash" => protocol.headers_to_include_in_hash = Some(value),
+            "responseCodes" => protocol.response_codes = Some(value),
+            "enableCacheControl" => protocol.enable_cache_control = Some(value.parse()?),
+            "includeAgeHeader" => protocol.include_age_header = Some(value.parse()?),
+            "hashGenerator" => protocol.hash_generator = Some(value),
+            _ => {
+                bail!(
+                    "not a supported cache protocol element: element {}",
+                    child.name
+                );
+            }
+        }
+    }
+
+    Result::Ok(protocol)
+}
+
+//namespace declarations in scope of a child of the element with the given scope whose prefixes
+//are used by the value, e.g. the s11 prefix of the xpath s11:Envelope/s11:Body
+fn used_namespaces(
+    scope: &Namespace,
+    element: &ast::RawElement,
+    value: &str,
+) -> Vec<(String, String)> {
+    let mut in_scope = scope.0.clone();
+    in_scope.extend(element.namespaces.iter().cloned());
+    in_scope
+        .into_iter()
+        .filter(|(prefix, _)| !matches!(prefix.as_str(), "" | "xml" | "xmlns"))
+        .filter(|(prefix, _)| value.contains(&format!("{}:", prefix)))
+        .collect()
+}
+
+fn parse_handler(element: &ast::RawElement) -> Result<ast::Handler> {
+    if element.name != "handler" {
+        bail!("not a supported handlers element: element {}", element.name);
+    }
+
+    let mut handler = ast::Handler {
+        class: element
+            .attribute("class")
+            .context("handler without class")?
+            .to_string(),
+        properties: Vec::new(),
+    };
+    for child in element.elements() {
+        if child.name != "property" {
+            bail!("not a supported handler element: element {}", child.name);
+        }
+        handler.properties.push(parse_raw_property(child)?);
+    }
+    Result::Ok(handler)
+}
+
+fn parse_raw_property(element: &ast::RawElement) -> Result<ast::PropertyMediator> {
+    Result::Ok(ast::PropertyMediator {
+        name: element
+            .attribute("name")
+            .context("property without name")?
+            .to_string(),
+        action: parse_attribute(element, "action")?,
+        value: element.attribute("value").unwrap_or_default().to_string(),
+        expression: element.attribute("expression").map(String::from),
+        scope: parse_attribute(element, "scope")?,
+        kind: element.attribute("type").map(String::from),
+        pattern: element.attribute("pattern").map(String::from),
+        group: parse_attribute(element, "group")?,
+        inline: element.children.clone(),
+    })
+}
+
+fn parse_parameter(element: &ast::RawElement) -> Result<ast::Parameter> {
+    Result::Ok(ast::Parameter {
+        name: element
+            .attribute("name")
+            .context("parameter without name")?
+            .to_string(),
+        value: element.text(),
+    })
+}
+
+//parameter children of message stores and processors
+fn parse_parameters(element: &ast::RawElement) -> Result<Vec<ast::Parameter>> {
+    element
+        .elements()
+        .map(|child| match child.name.as_str() {
+            "parameter" => parse_parameter(child),
+            _ => bail!("not a supported parameter: element {}", child.name),
+        })
+        .collect()
+}
+
+fn parse_fault_value(element: &ast::RawElement) -> Result<ast::FaultValue> {
+    match (element.attribute("value"), element.attribute("expression")) {
+        (Some(value), None) => Result::Ok(ast::FaultValue::Value(value.to_string())),
+        (None, Some(expression)) => Result::Ok(ast::FaultValue::Expression(expression.to_string())),
+        _ => bail!(
+            "fault {} needs either a value or an expression",
+            element.name
+        ),
+    }
+}
+
+type MediatorParser = Box<dyn Fn(&ast::RawElement) -> Result<Box<dyn ast::CustomMediator>>>;
+
+struct Extension {
+    namespace: Option<String>,
+    name: String,
+    parse: MediatorParser,
+}
+
+//result of Parser::parse_partial, the program holds the root elements parsed before the error
+#[derive(Debug)]
+pub struct PartialProgram {
+    pub program: ast::Program,
+    pub error: Option<ParseError>,
+}
+
+//row and column are zero based and point at the reader position when parsing stopped
+#[derive(Debug)]
+pub struct ParseError {
+    pub error: anyhow::Error,
+    pub row: u64,
+    pub column: u64,
+}
+
+//records the byte order mark and line endings of the bytes passing through
+struct SourceReader<R> {
+    inner: R,
+    //first bytes of the input, up to the length of a byte order mark
+    head: Vec<u8>,
+    previous_cr: bool,
+    format: ast::SourceFormat,
+}
+
+impl<R> SourceReader<R> {
+    fn inspect(&mut self, bytes: &[u8]) {
+        if bytes.is_empty() {
+            return;
+        }
+        if self.head.len() < 3 {
+            let missing = (3 - self.head.len()).min(bytes.len());
+            self.head.extend(&bytes[..missing]);
+            self.format.bom = self.head == [0xEF, 0xBB, 0xBF];
+        }
+        if !self.format.crlf {
+            self.format.crlf = (self.previous_cr && bytes[0] == b'\n')
+                || bytes.windows(2).any(|window| window == b"\r\n");
+        }
+        self.previous_cr = bytes.last() == Some(&b'\r');
+    }
+}
+
+impl<R: BufRead> Read for SourceReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.inspect(&buf[..read]);
+        Result::Ok(read)
+    }
+}
+
+impl<R: BufRead> BufRead for SourceReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        //the buffer is still filled, so this does not read
+        if let Result::Ok(buffer) = self.inner.fill_buf() {
+            let consumed = buffer[..amt.min(buffer.len())].to_vec();
+            self.inspect(&consumed);
+        }
+        self.inner.consume(amt);
+    }
+}
+
+pub struct Parser<R: BufRead> {
+    event_reader: EventReader<SourceReader<R>>,
     current_event: Option<XmlEvent>,
+    verbatim: bool,
+    allow_fragments: bool,
+    extensions: Vec<Extension>,
 }
 
 impl<R: BufRead> Parser<R> {
     pub fn new(input: R) -> Self {
         let mut parser = Parser {
-            event_reader: ParserConfig::new()
-                .trim_whitespace(true)
-                .create_reader(input),
+            //whitespace is dropped in next_event, except inside verbatim elements like script bodies
+            event_reader: ParserConfig::new().create_reader(SourceReader {
+                inner: input,
+                head: Vec::new(),
+                previous_cr: false,
+                format: ast::SourceFormat::default(),
+            }),
             current_event: None,
+            verbatim: false,
+            allow_fragments: false,
+            extensions: Vec::new(),
         };
 
-        let curr = parser.event_reader.next();
-
-        if curr.is_ok() {
-            parser.current_event = Some(curr.unwrap());
-        }
+        parser.next_event();
 
         parser
     }
+
+    //accept bare mediators at the document root (snippets, registry fragments)
+    pub fn allow_fragments(mut self, allow: bool) -> Self {
+        self.allow_fragments = allow;
+        self
+    }
+
+    //parse elements with the given name (and namespace if given) into custom mediators
+    pub fn register_mediator<F>(mut self, namespace: Option<&str>, name: &str, parse: F) -> Self
+    where
+        F: Fn(&ast::RawElement) -> Result<Box<dyn ast::CustomMediator>> + 'static,
+    {
+        self.extensions.push(Extension {
+            namespace: namespace.map(String::from),
+            name: name.to_string(),
+            parse: Box::new(parse),
+        });
+        self
+    }
+
     pub fn parse_progarm(&mut self) -> Result<ast::Program> {
-        //skip start document event
-        if self.current_event
-            == Some(XmlEvent::StartDocument {
-                version: xml::common::XmlVersion::Version10,
-                encoding: "UTF-8".to_string(),
-                standalone: None,
-            })
-        {
-            self.current_event = self.event_reader.next().ok();
-        }
+        let mut ast_nodes: Vec<ast::AstNode> = Vec::new();
+        self.parse_ast_nodes(&mut ast_nodes)?;
+        Result::Ok(ast::Program {
+            ast_nodes,
+            format: self.event_reader.source().format,
+        })
+    }
 
+    //like parse_progarm, but keeps the root elements parsed before an error
+    pub fn parse_partial(&mut self) -> PartialProgram {
         let mut ast_nodes: Vec<ast::AstNode> = Vec::new();
+        let error = self.parse_ast_nodes(&mut ast_nodes).err().map(|error| {
+            let position = self.event_reader.position();
+            ParseError {
+                error,
+                row: position.row,
+                column: position.column,
+            }
+        });
+
+        PartialProgram {
+            program: ast::Program {
+                ast_nodes,
+                format: self.event_reader.source().format,
+            },
+            error,
+        }
+    }
+
+    fn parse_ast_nodes(&mut self, ast_nodes: &mut Vec<ast::AstNode>) -> Result<()> {
+        self.skip_start_document();
+
+        //parse all elements
+        while self.current_event.as_ref() != Some(&XmlEvent::EndDocument) {
+            let node = match self.current_event.as_ref() {
+                Some(XmlEvent::StartElement { name, .. }) if name.local_name == "api" => {
+                    self.parse_api()
+                }
+                Some(XmlEvent::StartElement { name, .. }) if name.local_name == "proxy" => {
+                    self.parse_proxy()
+                }
+                Some(XmlEvent::StartElement { name, .. }) if name.local_name == "endpoint" => {
+                    self.parse_named_endpoint()
+                }
+                Some(XmlEvent::StartElement { name, .. }) if name.local_name == "messageStore" => {
+                    self.parse_message_store()
+                }
+                Some(XmlEvent::StartElement { name, .. })
+                    if name.local_name == "messageProcessor" =>
+                {
+                    self.parse_message_processor()
+                }
+                Some(XmlEvent::StartElement { name, .. })
+                    if matches!(
+                        name.local_name.as_str(),
+                        "inSequence" | "outSequence" | "faultSequence"
+                    ) =>
+                {
+                    self.parse_sequence().map(ast::AstNode::Sequence)
+                }
+                //<sequence key> without a name is a sequence mediator
+                Some(XmlEvent::StartElement {
+                    name, attributes, ..
+                }) if name.local_name == "sequence" && attribute(attributes, "name").is_some() => {
+                    self.parse_named_sequence().map(ast::AstNode::Sequence)
+                }
+                Some(XmlEvent::StartElement { .. }) if self.allow_fragments => {
+                    self.parse_mediator()
+                }
+                _ => {
+                    bail!("error");
+                }
+            };
+            ast_nodes.push(node?);
+        }
+        Result::Ok(())
+    }
+
+    fn parse_fragment(&mut self, context: FragmentContext) -> Result<Fragment> {
+        self.skip_start_document();
+
+        //current event is start element of the wrapping fragment element
+        self.next_event();
+
+        match context {
+            FragmentContext::Endpoint => {
+                let endpoint = match self.current_event.as_ref() {
+                    Some(XmlEvent::StartElement { name, .. }) if name.local_name == "endpoint" => {
+                        self.parse_endpoint_ref()?
+                    }
+                    _ => {
+                        bail!("expected an endpoint");
+                    }
+                };
+                if !self.at_end_of("fragment") {
+                    bail!("expected a single endpoint");
+                }
+                Result::Ok(Fragment::Endpoint(endpoint))
+            }
+            FragmentContext::Mediator => {
+                let mut mediators = self.parse_mediators_until("fragment")?;
+                if mediators.len() != 1 {
+                    bail!("expected a single mediator, found {}", mediators.len());
+                }
+                Result::Ok(Fragment::Mediator(mediators.remove(0)))
+            }
+            FragmentContext::Mediators => {
+                Result::Ok(Fragment::Mediators(self.parse_mediators_until("fragment")?))
+            }
+        }
+    }
+
+    //--------------------------------------------------------------------------------//
+
+    fn next_event(&mut self) {
+        self.current_event = self.event_reader.next().ok();
+        if !self.verbatim {
+            self.skip_whitespace();
+        }
+    }
+
+    //drop formatting whitespace between elements and around text
+    fn skip_whitespace(&mut self) {
+        loop {
+            match self.current_event.take() {
+                Some(XmlEvent::Whitespace(_)) => {
+                    self.current_event = self.event_reader.next().ok();
+                }
+                Some(XmlEvent::Characters(text)) => {
+                    self.current_event = Some(XmlEvent::Characters(text.trim().to_string()));
+                    break;
+                }
+                event => {
+                    self.current_event = event;
+                    break;
+                }
+            }
+        }
+    }
+
+    //like parse_raw_element, but keeps whitespace and cdata sections of the content as written
+    fn parse_verbatim_element(&mut self) -> Result<ast::RawElement> {
+        self.verbatim = true;
+        let element = self.parse_raw_element();
+        self.verbatim = false;
+        self.skip_whitespace();
+        element
+    }
+
+    //capture the current element including its children as is, the end element is consumed
+    fn parse_raw_element(&mut self) -> Result<ast::RawElement> {
+        self.parse_raw_element_in(None)
+    }
+
+    //namespace declarations are only known relative to the parent element, the declarations of
+    //the outermost element are not captured
+    fn parse_raw_element_in(&mut self, parent: Option<&Namespace>) -> Result<ast::RawElement> {
+        let (mut element, scope) = match self.current_event.take() {
+            Some(XmlEvent::StartElement {
+                name,
+                attributes,
+                namespace,
+            }) => (
+                ast::RawElement {
+                    prefix: name.prefix,
+                    namespace: name.namespace,
+                    name: name.local_name,
+                    attributes: attributes
+                        .into_iter()
+                        .map(|attr| match attr.name.prefix {
+                            Some(prefix) => {
+                                (format!("{}:{}", prefix, attr.name.local_name), attr.value)
+                            }
+                            None => (attr.name.local_name, attr.value),
+                        })
+                        .collect(),
+                    namespaces: match parent {
+                        Some(parent) => namespace
+                            .0
+                            .iter()
+                            .filter(|(prefix, uri)| parent.get(prefix) != Some(uri.as_str()))
+                            .map(|(prefix, uri)| (prefix.clone(), uri.clone()))
+                            .collect(),
+                        None => Vec::new(),
+                    },
+                    children: Vec::new(),
+                },
+                namespace,
+            ),
+            _ => {
+                bail!("expected start element");
+            }
+        };
+
+        self.next_event();
+        loop {
+            match self.current_event.take() {
+                Some(XmlEvent::EndElement { .. }) => break,
+                Some(event @ XmlEvent::StartElement { .. }) => {
+                    self.current_event = Some(event);
+                    let child = self.parse_raw_element_in(Some(&scope))?;
+                    element.children.push(ast::RawNode::Element(child));
+                    continue;
+                }
+                Some(XmlEvent::Characters(text)) | Some(XmlEvent::Whitespace(text)) => {
+                    element.children.push(ast::RawNode::Text(text));
+                }
+                Some(XmlEvent::CData(text)) => {
+                    element.children.push(ast::RawNode::CData(text));
+                }
+                Some(_) => {}
+                None => {
+                    bail!("unexpected end of element {}", element.name);
+                }
+            }
+            self.next_event();
+        }
+
+        self.next_event();
+
+        Result::Ok(element)
+    }
+
+    fn skip_start_document(&mut self) {
+        if let Some(XmlEvent::StartDocument { .. }) = self.current_event {
+            self.next_event();
+        }
+    }
+
+    fn at_end_of(&self, local_name: &str) -> bool {
+        matches!(self.current_event.as_ref(), Some(XmlEvent::EndElement { name }) if name.local_name == local_name)
+    }
+
+    //parse mediators until the end element of the enclosing element, the end element is consumed
+    fn parse_mediators_until(&mut self, end_element: &str) -> Result<Vec<ast::Mediators>> {
+        let mut mediators = Vec::new();
+
+        while !self.at_end_of(end_element) {
+            let mediator = self.parse_mediator().context("error parsing mediator")?;
+            match mediator {
+                ast::AstNode::Mediator(mediator) => {
+                    mediators.push(mediator);
+                }
+                _ => {
+                    bail!("error parsing mediator");
+                }
+            }
+        }
+
+        self.next_event();
+
+        Result::Ok(mediators)
+    }
+
+    //--------------------------------------------------------------------------------//
+
+    fn parse_api(&mut self) -> Result<ast::AstNode> {
+        let mut api = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => ast::Api {
+                context: attribute(attributes, "context").context("api without context")?,
+                name: attribute(attributes, "name").context("api without name")?,
+                xmlns: name.namespace.clone(),
+                trace: parse_attribute(attributes, "trace")?,
+                statistics: parse_attribute(attributes, "statistics")?,
+                version: attribute(attributes, "version"),
+                version_type: parse_attribute(attributes, "version-type")?,
+                resources: Vec::new(),
+                handlers: Vec::new(),
+            },
+            _ => {
+                bail!("not an api");
+            }
+        };
+
+        //current event is start element of api walk to the next event (start element of resource)
+        self.next_event();
+        while !self.at_end_of("api") {
+            match self.current_event.as_ref() {
+                Some(XmlEvent::StartElement { name, .. }) if name.local_name == "resource" => {
+                    let resource = self.parse_resource().context("error parsing resource")?;
+                    api.resources.push(resource);
+                }
+                Some(XmlEvent::StartElement { name, .. }) if name.local_name == "handlers" => {
+                    for handler in self.parse_raw_element()?.elements() {
+                        api.handlers.push(parse_handler(handler)?);
+                    }
+                }
+                Some(XmlEvent::StartElement { name, .. }) => {
+                    bail!("not a supported api element: element {}", name.local_name);
+                }
+                _ => {
+                    bail!("error parsing api {}", api.name);
+                }
+            }
+        }
+
+        self.next_event();
+
+        Result::Ok(ast::AstNode::Api(api))
+    }
+
+    fn parse_resource(&mut self) -> Result<ast::Resource> {
+        let mut resource = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { attributes, .. }) => ast::Resource {
+                methods: attribute(attributes, "methods")
+                    .unwrap_or_default()
+                    .split_whitespace()
+                    .map(str::parse)
+                    .collect::<Result<_>>()?,
+                uri_template: attribute(attributes, "uri-template"),
+                url_mapping: attribute(attributes, "url-mapping"),
+                sequences: Vec::new(),
+            },
+            _ => {
+                bail!("not a resource");
+            }
+        };
+
+        //current event is start element of resource walk to the next event (start element of sequence)
+        self.next_event();
+        while !self.at_end_of("resource") {
+            resource.sequences.push(self.parse_sequence()?);
+        }
+
+        self.next_event();
+
+        Result::Ok(resource)
+    }
+
+    //--------------------------------------------------------------------------------//
+
+    fn parse_proxy(&mut self) -> Result<ast::AstNode> {
+        let mut proxy = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => ast::Proxy {
+                name: attribute(attributes, "name").context("proxy without name")?,
+                xmlns: name.namespace.clone(),
+                transports: attribute(attributes, "transports")
+                    .unwrap_or_default()
+                    .split([' ', ','])
+                    .filter(|transport| !transport.is_empty())
+                    .map(String::from)
+                    .collect(),
+                start_on_load: parse_attribute(attributes, "startOnLoad")?,
+                trace: parse_attribute(attributes, "trace")?,
+                statistics: parse_attribute(attributes, "statistics")?,
+                description: None,
+                target: None,
+                publish_wsdl: None,
+                parameters: Vec::new(),
+            },
+            _ => {
+                bail!("not a proxy");
+            }
+        };
+
+        //current event is start element of proxy walk to the next event (start element of target)
+        self.next_event();
+        while !self.at_end_of("proxy") {
+            let element = match self.current_event.as_ref() {
+                Some(XmlEvent::StartElement { name, .. }) => name.local_name.clone(),
+                _ => {
+                    bail!("error parsing proxy {}", proxy.name);
+                }
+            };
+            match element.as_str() {
+                "target" => proxy.target = Some(self.parse_proxy_target()?),
+                "publishWSDL" => proxy.publish_wsdl = Some(self.parse_publish_wsdl()?),
+                "parameter" => {
+                    let parameter = self.parse_raw_element()?;
+                    proxy.parameters.push(parse_parameter(&parameter)?);
+                }
+                "description" => proxy.description = Some(self.parse_raw_element()?.text()),
+                _ => {
+                    bail!("not a supported proxy element: element {}", element);
+                }
+            }
+        }
+
+        self.next_event();
+
+        Result::Ok(ast::AstNode::Proxy(Box::new(proxy)))
+    }
+
+    fn parse_message_store(&mut self) -> Result<ast::AstNode> {
+        let element = self.parse_raw_element()?;
+        let name = element
+            .attribute("name")
+            .context("message store without name")?;
+
+        let message_store = ast::MessageStore {
+            name: name.to_string(),
+            xmlns: element.namespace.clone(),
+            class: element.attribute("class").map(String::from),
+            parameters: parse_parameters(&element)
+                .with_context(|| format!("error parsing message store {}", name))?,
+        };
+
+        Result::Ok(ast::AstNode::MessageStore(message_store))
+    }
+
+    fn parse_message_processor(&mut self) -> Result<ast::AstNode> {
+        let element = self.parse_raw_element()?;
+        let name = element
+            .attribute("name")
+            .context("message processor without name")?;
+
+        let message_processor = ast::MessageProcessor {
+            name: name.to_string(),
+            xmlns: element.namespace.clone(),
+            class: element
+                .attribute("class")
+                .with_context(|| format!("message processor {} without class", name))?
+                .to_string(),
+            message_store: element
+                .attribute("messageStore")
+                .with_context(|| format!("message processor {} without messageStore", name))?
+                .to_string(),
+            target_endpoint: element.attribute("targetEndpoint").map(String::from),
+            parameters: parse_parameters(&element)
+                .with_context(|| format!("error parsing message processor {}", name))?,
+        };
+
+        Result::Ok(ast::AstNode::MessageProcessor(message_processor))
+    }
+
+    fn parse_proxy_target(&mut self) -> Result<ast::ProxyTarget> {
+        let mut target = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { attributes, .. }) => ast::ProxyTarget {
+                in_sequence: attribute(attributes, "inSequence"),
+                out_sequence: attribute(attributes, "outSequence"),
+                fault_sequence: attribute(attributes, "faultSequence"),
+                endpoint: attribute(attributes, "endpoint").map(ast::EndpointRef::Key),
+                sequences: Vec::new(),
+            },
+            _ => {
+                bail!("not a target");
+            }
+        };
+
+        self.next_event();
+        while !self.at_end_of("target") {
+            match self.current_event.as_ref() {
+                Some(XmlEvent::StartElement { name, .. }) if name.local_name == "endpoint" => {
+                    target.endpoint = Some(self.parse_endpoint_ref()?);
+                }
+                _ => target.sequences.push(self.parse_sequence()?),
+            }
+        }
+
+        self.next_event();
+
+        Result::Ok(target)
+    }
+
+    fn parse_publish_wsdl(&mut self) -> Result<ast::PublishWsdl> {
+        let element = self.parse_raw_element()?;
+
+        let mut publish_wsdl = ast::PublishWsdl {
+            uri: element.attribute("uri").map(String::from),
+            key: element.attribute("key").map(String::from),
+            preserve_policy: element
+                .attribute("preservePolicy")
+                .map(str::parse)
+                .transpose()
+                .context("invalid value for attribute preservePolicy")?,
+            definition: None,
+            resources: Vec::new(),
+        };
+
+        for child in element.children {
+            match child {
+                ast::RawNode::Element(resource) if resource.name == "resource" => {
+                    publish_wsdl.resources.push(ast::WsdlResource {
+                        location: resource.attribute("location").map(String::from),
+                        key: resource.attribute("key").map(String::from),
+                    });
+                }
+                //inline wsdl:definitions or wsdl2 description
+                ast::RawNode::Element(definition) => publish_wsdl.definition = Some(definition),
+                ast::RawNode::Text(_) | ast::RawNode::CData(_) => {}
+            }
+        }
+
+        Result::Ok(publish_wsdl)
+    }
+
+    //--------------------------------------------------------------------------------//
+
+    fn parse_sequence(&mut self) -> Result<ast::Sequences> {
+        match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { name, .. }) => match name.local_name.as_str() {
+                "inSequence" => self.parse_in_sequence(),
+                "outSequence" => self.parse_out_sequence(),
+                "faultSequence" => self.parse_fault_sequence(),
+                _ => {
+                    bail!("not a supported sequence: element {}", name.local_name);
+                }
+            },
+            _ => {
+                bail!("not a supported sequence");
+            }
+        }
+    }
+
+    fn parse_named_sequence(&mut self) -> Result<ast::Sequences> {
+        let mut named_sequence = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement {
+                name, attributes, ..
+            }) => ast::NamedSequence {
+                name: attribute(attributes, "name").context("sequence without name")?,
+                xmlns: name.namespace.clone(),
+                on_error: attribute(attributes, "onError"),
+                trace: parse_attribute(attributes, "trace")?,
+                statistics: parse_attribute(attributes, "statistics")?,
+                mediators: Vec::new(),
+            },
+            _ => {
+                bail!("not a sequence");
+            }
+        };
+
+        self.next_event();
+        named_sequence.mediators = self
+            .parse_mediators_until("sequence")
+            .with_context(|| format!("error parsing sequence {}", named_sequence.name))?;
+
+        Result::Ok(ast::Sequences::NamedSequence(named_sequence))
+    }
+
+    fn parse_in_sequence(&mut self) -> Result<ast::Sequences> {
+        //current event is start element of inSequence walk to the next event (start element of mediator)
+        self.next_event();
+        let in_sequence = ast::InSequence {
+            mediators: self.parse_mediators_until("inSequence")?,
+        };
+
+        Result::Ok(ast::Sequences::InSequence(in_sequence))
+    }
+
+    fn parse_out_sequence(&mut self) -> Result<ast::Sequences> {
+        self.next_event();
+        let out_sequence = ast::OutSequence {
+            mediators: self.parse_mediators_until("outSequence")?,
+        };
+
+        Result::Ok(ast::Sequences::OutSequence(out_sequence))
+    }
+
+    fn parse_fault_sequence(&mut self) -> Result<ast::Sequences> {
+        self.next_event();
+        let fault_sequence = ast::FaultSequence {
+            mediators: self.parse_mediators_until("faultSequence")?,
+        };
+
+        Result::Ok(ast::Sequences::FaultSequence(fault_sequence))
+    }
+
+    //--------------------------------------------------------------------------------//
+
+    fn parse_mediator(&mut self) -> Result<ast::AstNode> {
+        let extension = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { name, .. }) => {
+                //extensions registered for the element's namespace take precedence over built-ins
+                let qualified = self.extensions.iter().position(|extension| {
+                    extension.name == name.local_name
+                        && extension.namespace.is_some()
+                        && extension.namespace == name.namespace
+                });
+                let unqualified = self.extensions.iter().position(|extension| {
+                    extension.name == name.local_name && extension.namespace.is_none()
+                });
+                match (qualified, name.namespace.as_deref()) {
+                    (Some(index), _) => Some(index),
+                    //built-in mediators live in the synapse namespace or in no namespace
+                    (None, None | Some(codegen::SYNAPSE_NAMESPACE)) => None,
+                    (None, Some(namespace)) if unqualified.is_none() => bail!(
+                        "not a supported mediator: element {{{}}}{}",
+                        namespace,
+                        name.local_name
+                    ),
+                    (None, Some(_)) => unqualified,
+                }
+            }
+            _ => None,
+        };
+        if let Some(index) = extension {
+            return self.parse_extension(index);
+        }
+
+        match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { name, .. }) => match name.local_name.as_str() {
+                "log" => self.parse_log_mediator(),
+                "property" => self.parse_property(),
+                "call" => self.parse_call_mediator(),
+                "send" => self.parse_send_mediator(),
+                "callout" => self.parse_callout_mediator(),
+                "store" => self.parse_store_mediator(),
+                "makefault" => self.parse_makefault_mediator(),
+                "filter" => self.parse_filter_mediator(),
+                "switch" => self.parse_switch_mediator(),
+                "payloadFactory" => self.parse_payload_factory_mediator(),
+                "header" => self.parse_header_mediator(),
+                "class" => self.parse_class_mediator(),
+                "sequence" => self.parse_sequence_mediator(),
+                "enrich" => self.parse_enrich_mediator(),
+                "xslt" => self.parse_xslt_mediator(),
+                "xquery" => self.parse_xquery_mediator(),
+                "validate" => self.parse_validate_mediator(),
+                "script" => self.parse_script_mediator(),
+                "iterate" => self.parse_iterate_mediator(),
+                "aggregate" => self.parse_aggregate_mediator(),
+                "clone" => self.parse_clone_mediator(),
+                "cache" => self.parse_cache_mediator(),
+                "respond" => self.parse_empty_mediator(ast::Mediators::Respond),
+                "drop" => self.parse_empty_mediator(ast::Mediators::Drop),
+                "loopback" => self.parse_empty_mediator(ast::Mediators::Loopback),
+                _ => {
+                    let extension = self.extensions.iter().position(|extension| {
+                        extension.name == name.local_name && extension.namespace.is_none()
+                    });
+                    match extension {
+                        Some(index) => self.parse_extension(index),
+                        None if catalog::mediator(&name.local_name).is_some() => {
+                            let element = self.parse_raw_element()?;
+                            Result::Ok(ast::AstNode::Mediator(ast::Mediators::Unparsed(element)))
+                        }
+                        None => {
+                            bail!("not a supported mediator: element {}", name.local_name);
+                        }
+                    }
+                }
+            },
+            _ => {
+                bail!("not a supported mediator");
+            }
+        }
+    }
+
+    fn parse_extension(&mut self, index: usize) -> Result<ast::AstNode> {
+        let element = self.parse_raw_element()?;
+        let mediator = (self.extensions[index].parse)(&element)
+            .with_context(|| format!("error parsing custom mediator {}", element.name))?;
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Extension(mediator)))
+    }
+
+    //mediators without attributes or children, e.g. <drop/> or <drop></drop>
+    fn parse_empty_mediator(&mut self, mediator: ast::Mediators) -> Result<ast::AstNode> {
+        let element = self.parse_raw_element()?;
+        if let Some(child) = element.elements().next() {
+            bail!(
+                "not a supported {} element: element {}",
+                element.name,
+                child.name
+            );
+        }
+
+        Result::Ok(ast::AstNode::Mediator(mediator))
+    }
+
+    fn parse_log_mediator(&mut self) -> Result<ast::AstNode> {
+        let mut log_level = String::new();
+
+        //get log level
+        match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { attributes, .. }) => {
+                for attr in attributes {
+                    if attr.name.local_name == "level" {
+                        log_level = attr.value.clone();
+                    }
+                }
+            }
+            _ => {
+                bail!("not log level specified");
+            }
+        }
+
+        //create log mediator node
+        let mut log_mediator = ast::LogMediator {
+            level: log_level,
+            properties: vec![],
+        };
+
+        //current event is start element of log mediator walk to the next event (start element of property mediator)
+        self.next_event();
+
+        //parse log content properties
+        while !self.at_end_of("log") {
+            match self.parse_mediator() {
+                Result::Ok(ast::AstNode::Mediator(ast::Mediators::Property(property))) => {
+                    log_mediator.properties.push(property);
+                }
+                _ => {
+                    bail!("error parsing log mediator");
+                }
+            }
+        }
+
+        self.next_event();
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Log(log_mediator)))
+    }
+
+    fn parse_class_mediator(&mut self) -> Result<ast::AstNode> {
+        let mut class_mediator = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { attributes, .. }) => ast::ClassMediator {
+                name: attribute(attributes, "name").context("class mediator without name")?,
+                properties: Vec::new(),
+            },
+            _ => {
+                bail!("not a class mediator");
+            }
+        };
+
+        self.next_event();
+        while !self.at_end_of("class") {
+            match self.parse_mediator() {
+                Result::Ok(ast::AstNode::Mediator(ast::Mediators::Property(property))) => {
+                    class_mediator.properties.push(property);
+                }
+                _ => {
+                    bail!("error parsing class mediator {}", class_mediator.name);
+                }
+            }
+        }
+
+        self.next_event();
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Class(
+            class_mediator,
+        )))
+    }
+
+    fn parse_sequence_mediator(&mut self) -> Result<ast::AstNode> {
+        let element = self.parse_raw_element()?;
+        if let Some(child) = element.elements().next() {
+            bail!("not a supported sequence element: element {}", child.name);
+        }
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Sequence(
+            ast::SequenceMediator {
+                key: element
+                    .attribute("key")
+                    .context("sequence mediator without key")?
+                    .to_string(),
+            },
+        )))
+    }
+
+    fn parse_enrich_mediator(&mut self) -> Result<ast::AstNode> {
+        let element = self.parse_raw_element()?;
+        let mut source = None;
+        let mut target = None;
+
+        for child in element.elements() {
+            match child.name.as_str() {
+                "source" => {
+                    source = Some(ast::EnrichSource {
+                        clone: parse_attribute(child, "clone")?,
+                        kind: parse_attribute(child, "type")?,
+                        xpath: child.attribute("xpath").map(String::from),
+                        property: child.attribute("property").map(String::from),
+                        key: child.attribute("key").map(String::from),
+                        inline: child.children.clone(),
+                    });
+                }
+                "target" => {
+                    target = Some(ast::EnrichTarget {
+                        action: parse_attribute(child, "action")?,
+                        kind: parse_attribute(child, "type")?,
+                        xpath: child.attribute("xpath").map(String::from),
+                        property: child.attribute("property").map(String::from),
+                    });
+                }
+                _ => {
+                    bail!("not a supported enrich element: element {}", child.name);
+                }
+            }
+        }
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Enrich(
+            ast::EnrichMediator {
+                source: source.context("enrich without source")?,
+                target: target.context("enrich without target")?,
+            },
+        )))
+    }
+
+    fn parse_xslt_mediator(&mut self) -> Result<ast::AstNode> {
+        let element = self.parse_raw_element()?;
+        let mut xslt_mediator = ast::XsltMediator {
+            key: element
+                .attribute("key")
+                .context("xslt mediator without key")?
+                .to_string(),
+            source: element.attribute("source").map(String::from),
+            properties: Vec::new(),
+            features: Vec::new(),
+        };
+
+        for child in element.elements() {
+            let name = child
+                .attribute("name")
+                .with_context(|| format!("{} without name", child.name))?
+                .to_string();
+            match child.name.as_str() {
+                "property" => xslt_mediator.properties.push(parse_raw_property(child)?),
+                "feature" => xslt_mediator.features.push(ast::XsltFeature {
+                    name,
+                    value: parse_attribute(child, "value")?.context("feature without value")?,
+                }),
+                _ => {
+                    bail!("not a supported xslt element: element {}", child.name);
+                }
+            }
+        }
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Xslt(xslt_mediator)))
+    }
+
+    fn parse_xquery_mediator(&mut self) -> Result<ast::AstNode> {
+        let element = self.parse_raw_element()?;
+        let mut xquery_mediator = ast::XqueryMediator {
+            key: element
+                .attribute("key")
+                .context("xquery mediator without key")?
+                .to_string(),
+            target: element.attribute("target").map(String::from),
+            variables: Vec::new(),
+        };
+
+        for child in element.elements() {
+            if child.name != "variable" {
+                bail!("not a supported xquery element: element {}", child.name);
+            }
+            xquery_mediator.variables.push(ast::XqueryVariable {
+                name: child
+                    .attribute("name")
+                    .context("variable without name")?
+                    .to_string(),
+                kind: child
+                    .attribute("type")
+                    .context("variable without type")?
+                    .to_string(),
+                value: child.attribute("value").map(String::from),
+                expression: child.attribute("expression").map(String::from),
+                key: child.attribute("key").map(String::from),
+            });
+        }
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Xquery(
+            xquery_mediator,
+        )))
+    }
+
+    fn parse_validate_mediator(&mut self) -> Result<ast::AstNode> {
+        let mut validate_mediator = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { attributes, .. }) => ast::ValidateMediator {
+                source: attribute(attributes, "source"),
+                schemas: Vec::new(),
+                properties: Vec::new(),
+                on_fail: Vec::new(),
+            },
+            _ => {
+                bail!("not a validate mediator");
+            }
+        };
+
+        self.next_event();
+        while !self.at_end_of("validate") {
+            match self.current_event.as_ref() {
+                Some(XmlEvent::StartElement { name, .. }) if name.local_name == "schema" => {
+                    let schema = self.parse_raw_element()?;
+                    validate_mediator.schemas.push(
+                        schema
+                            .attribute("key")
+                            .context("schema without key")?
+                            .to_string(),
+                    );
+                }
+                Some(XmlEvent::StartElement { name, .. }) if name.local_name == "property" => {
+                    match self.parse_property()? {
+                        ast::AstNode::Mediator(ast::Mediators::Property(property)) => {
+                            validate_mediator.properties.push(property);
+                        }
+                        _ => {
+                            bail!("error parsing validate mediator");
+                        }
+                    }
+                }
+                Some(XmlEvent::StartElement { name, .. }) if name.local_name == "on-fail" => {
+                    self.next_event();
+                    validate_mediator.on_fail = self.parse_mediators_until("on-fail")?;
+                }
+                Some(XmlEvent::StartElement { name, .. }) => {
+                    bail!(
+                        "not a supported validate element: element {}",
+                        name.local_name
+                    );
+                }
+                _ => {
+                    bail!("error parsing validate mediator");
+                }
+            }
+        }
+
+        self.next_event();
+
+        if validate_mediator.schemas.is_empty() {
+            bail!("validate mediator without schema");
+        }
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Validate(
+            validate_mediator,
+        )))
+    }
+
+    fn parse_script_mediator(&mut self) -> Result<ast::AstNode> {
+        let element = self.parse_verbatim_element()?;
+        if let Some(child) = element.elements().next() {
+            bail!("not a supported script element: element {}", child.name);
+        }
+
+        //cdata sections hold the script as written, whitespace around them is only formatting
+        let cdata_only = element.children.iter().all(|child| match child {
+            ast::RawNode::CData(_) => true,
+            ast::RawNode::Text(text) => text.trim().is_empty(),
+            ast::RawNode::Element(_) => false,
+        });
+        let body = if cdata_only {
+            element
+                .children
+                .iter()
+                .filter_map(|child| match child {
+                    ast::RawNode::CData(text) => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect()
+        } else {
+            element.text()
+        };
+
+        let script_mediator = ast::ScriptMediator {
+            language: element
+                .attribute("language")
+                .context("script mediator without language")?
+                .to_string(),
+            key: element.attribute("key").map(String::from),
+            function: element.attribute("function").map(String::from),
+            body: Some(body).filter(|body| !body.trim().is_empty()),
+        };
+
+        match (&script_mediator.key, &script_mediator.body) {
+            (Some(_), None) | (None, Some(_)) => {}
+            _ => {
+                bail!("script mediator needs either a key or an inline script");
+            }
+        }
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Script(
+            script_mediator,
+        )))
+    }
+
+    fn parse_iterate_mediator(&mut self) -> Result<ast::AstNode> {
+        let attributes = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { attributes, .. }) => attributes.clone(),
+            _ => {
+                bail!("not an iterate mediator");
+            }
+        };
+
+        self.next_event();
+        let mut target = None;
+        while !self.at_end_of("iterate") {
+            match self.current_event.as_ref() {
+                Some(XmlEvent::StartElement { name, .. })
+                    if name.local_name == "target" && target.is_none() =>
+                {
+                    target = Some(self.parse_target()?);
+                }
+                Some(XmlEvent::StartElement { name, .. }) => {
+                    bail!(
+                        "not a supported iterate element: element {}",
+                        name.local_name
+                    );
+                }
+                _ => {
+                    bail!("error parsing iterate mediator");
+                }
+            }
+        }
+
+        self.next_event();
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Iterate(
+            ast::IterateMediator {
+                id: attribute(&attributes, "id"),
+                expression: attribute(&attributes, "expression")
+                    .context("iterate mediator without expression")?,
+                attach_path: attribute(&attributes, "attachPath"),
+                preserve_payload: parse_attribute(&attributes, "preservePayload")?,
+                sequential: parse_attribute(&attributes, "sequential")?,
+                continue_parent: parse_attribute(&attributes, "continueParent")?,
+                target: target.context("iterate mediator without target")?,
+            },
+        )))
+    }
+
+    fn parse_aggregate_mediator(&mut self) -> Result<ast::AstNode> {
+        let id = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { attributes, .. }) => attribute(attributes, "id"),
+            _ => {
+                bail!("not an aggregate mediator");
+            }
+        };
+
+        let mut correlate_on = None;
+        let mut complete_condition = None;
+        let mut on_complete = None;
+
+        self.next_event();
+        while !self.at_end_of("aggregate") {
+            match self.current_event.as_ref() {
+                Some(XmlEvent::StartElement { name, .. }) if name.local_name == "correlateOn" => {
+                    let element = self.parse_raw_element()?;
+                    correlate_on = Some(
+                        element
+                            .attribute("expression")
+                            .context("correlateOn without expression")?
+                            .to_string(),
+                    );
+                }
+                Some(XmlEvent::StartElement { name, .. })
+                    if name.local_name == "completeCondition" =>
+                {
+                    let element = self.parse_raw_element()?;
+                    let message_count = element
+                        .elements()
+                        .find(|child| child.name == "messageCount");
+                    complete_condition = Some(ast::CompleteCondition {
+                        timeout: parse_attribute(&element, "timeout")?,
+                        min_messages: message_count
+                            .and_then(|count| count.attribute("min"))
+                            .map(String::from),
+                        max_messages: message_count
+                            .and_then(|count| count.attribute("max"))
+                            .map(String::from),
+                    });
+                }
+                Some(XmlEvent::StartElement {
+                    name, attributes, ..
+                }) if name.local_name == "onComplete" => {
+                    let mut complete = ast::OnComplete {
+                        expression: attribute(attributes, "expression")
+                            .context("onComplete without expression")?,
+                        sequence: attribute(attributes, "sequence"),
+                        enclosing_element_property: attribute(
+                            attributes,
+                            "enclosingElementProperty",
+                        ),
+                        mediators: Vec::new(),
+                    };
+                    self.next_event();
+                    complete.mediators = self.parse_mediators_until("onComplete")?;
+                    on_complete = Some(complete);
+                }
+                Some(XmlEvent::StartElement { name, .. }) => {
+                    bail!(
+                        "not a supported aggregate element: element {}",
+                        name.local_name
+                    );
+                }
+                _ => {
+                    bail!("error parsing aggregate mediator");
+                }
+            }
+        }
+
+        self.next_event();
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Aggregate(
+            ast::AggregateMediator {
+                id,
+                correlate_on,
+                complete_condition,
+                on_complete: on_complete.context("aggregate mediator without onComplete")?,
+            },
+        )))
+    }
+
+    fn parse_cache_mediator(&mut self) -> Result<ast::AstNode> {
+        let mut cache_mediator = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { attributes, .. }) => ast::CacheMediator {
+                id: attribute(attributes, "id"),
+                collector: parse_attribute(attributes, "collector")?,
+                timeout: parse_attribute(attributes, "timeout")?,
+                max_message_size: parse_attribute(attributes, "maxMessageSize")?,
+                on_cache_hit: None,
+                protocol: None,
+                implementation: None,
+            },
+            _ => {
+                bail!("not a cache mediator");
+            }
+        };
+
+        self.next_event();
+        while !self.at_end_of("cache") {
+            match self.current_event.as_ref() {
+                Some(XmlEvent::StartElement {
+                    name, attributes, ..
+                }) if name.local_name == "onCacheHit" => {
+                    let sequence = attribute(attributes, "sequence");
+                    self.next_event();
+                    cache_mediator.on_cache_hit = Some(ast::OnCacheHit {
+                        sequence,
+                        mediators: self.parse_mediators_until("onCacheHit")?,
+                    });
+                }
+                Some(XmlEvent::StartElement { name, .. }) if name.local_name == "protocol" => {
+                    let element = self.parse_raw_element()?;
+                    cache_mediator.protocol = Some(parse_cache_protocol(&element)?);
+                }
+                Some(XmlEvent::StartElement { name, .. })
+                    if name.local_name == "implementation" =>
+                {
+                    let element = self.parse_raw_element()?;
+                    cache_mediator.implementation = Some(ast::CacheImplementation {
+                        kind: element.attribute("type").map(String::from),
+                        max_size: parse_attribute(&element, "maxSize")?,
+                    });
+                }
+                Some(XmlEvent::StartElement { name, .. }) => {
+                    bail!("not a supported cache element: element {}", name.local_name);
+                }
+                _ => {
+                    bail!("error parsing cache mediator");
+                }
+            }
+        }
+
+        self.next_event();
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Cache(Box::new(
+            cache_mediator,
+        ))))
+    }
+
+    fn parse_clone_mediator(&mut self) -> Result<ast::AstNode> {
+        let mut clone_mediator = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { attributes, .. }) => ast::CloneMediator {
+                id: attribute(attributes, "id"),
+                continue_parent: parse_attribute(attributes, "continueParent")?,
+                sequential: parse_attribute(attributes, "sequential")?,
+                targets: Vec::new(),
+            },
+            _ => {
+                bail!("not a clone mediator");
+            }
+        };
+
+        self.next_event();
+        while !self.at_end_of("clone") {
+            match self.current_event.as_ref() {
+                Some(XmlEvent::StartElement { name, .. }) if name.local_name == "target" => {
+                    clone_mediator.targets.push(self.parse_target()?);
+                }
+                Some(XmlEvent::StartElement { name, .. }) => {
+                    bail!("not a supported clone element: element {}", name.local_name);
+                }
+                _ => {
+                    bail!("error parsing clone mediator");
+                }
+            }
+        }
+
+        self.next_event();
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Clone(
+            clone_mediator,
+        )))
+    }
+
+    //target of iterate and clone, sequence and endpoint are given either by key or inline
+    fn parse_target(&mut self) -> Result<ast::Target> {
+        let mut target = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { attributes, .. }) => ast::Target {
+                to: attribute(attributes, "to"),
+                soap_action: attribute(attributes, "soapAction"),
+                sequence: attribute(attributes, "sequence").map(ast::SequenceRef::Key),
+                endpoint: attribute(attributes, "endpoint").map(ast::EndpointRef::Key),
+            },
+            _ => {
+                bail!("not a target");
+            }
+        };
+
+        self.next_event();
+        while !self.at_end_of("target") {
+            match self.current_event.as_ref() {
+                Some(XmlEvent::StartElement { name, .. })
+                    if name.local_name == "sequence" && target.sequence.is_none() =>
+                {
+                    self.next_event();
+                    target.sequence = Some(ast::SequenceRef::Inline(
+                        self.parse_mediators_until("sequence")?,
+                    ));
+                }
+                Some(XmlEvent::StartElement { name, .. })
+                    if name.local_name == "endpoint" && target.endpoint.is_none() =>
+                {
+                    target.endpoint = Some(self.parse_endpoint_ref()?);
+                }
+                Some(XmlEvent::StartElement { name, .. }) => {
+                    bail!(
+                        "not a supported target element: element {}",
+                        name.local_name
+                    );
+                }
+                _ => {
+                    bail!("error parsing target");
+                }
+            }
+        }
+
+        self.next_event();
+
+        Result::Ok(target)
+    }
+
+    fn parse_call_mediator(&mut self) -> Result<ast::AstNode> {
+        let mut call_mediator = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { attributes, .. }) => ast::CallMediator {
+                blocking: parse_attribute(attributes, "blocking")?,
+                endpoint: None,
+            },
+            _ => {
+                bail!("not a call mediator");
+            }
+        };
+
+        self.next_event();
+        while !self.at_end_of("call") {
+            match self.current_event.as_ref() {
+                Some(XmlEvent::StartElement { name, .. })
+                    if name.local_name == "endpoint" && call_mediator.endpoint.is_none() =>
+                {
+                    call_mediator.endpoint = Some(self.parse_endpoint_ref()?);
+                }
+                _ => {
+                    bail!("error parsing call mediator");
+                }
+            }
+        }
+
+        self.next_event();
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Call(call_mediator)))
+    }
+
+    fn parse_filter_mediator(&mut self) -> Result<ast::AstNode> {
+        let condition = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { attributes, .. }) => match (
+                attribute(attributes, "xpath"),
+                attribute(attributes, "source"),
+                attribute(attributes, "regex"),
+            ) {
+                (Some(xpath), None, None) => ast::FilterCondition::Xpath(xpath),
+                (None, Some(source), Some(regex)) => {
+                    ast::FilterCondition::SourceRegex { source, regex }
+                }
+                _ => {
+                    bail!("filter mediator needs either xpath or source and regex");
+                }
+            },
+            _ => {
+                bail!("not a filter mediator");
+            }
+        };
+
+        let mut filter_mediator = ast::FilterMediator {
+            condition,
+            then_mediators: Vec::new(),
+            else_mediators: None,
+        };
+
+        self.next_event();
+        while !self.at_end_of("filter") {
+            match self.current_event.as_ref() {
+                Some(XmlEvent::StartElement { name, .. }) if name.local_name == "then" => {
+                    self.next_event();
+                    filter_mediator.then_mediators = self.parse_mediators_until("then")?;
+                }
+                Some(XmlEvent::StartElement { name, .. }) if name.local_name == "else" => {
+                    self.next_event();
+                    filter_mediator.else_mediators = Some(self.parse_mediators_until("else")?);
+                }
+                _ => match self.parse_mediator().context("error parsing mediator")? {
+                    ast::AstNode::Mediator(mediator) => {
+                        filter_mediator.then_mediators.push(mediator);
+                    }
+                    _ => {
+                        bail!("error parsing filter mediator");
+                    }
+                },
+            }
+        }
+
+        self.next_event();
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Filter(
+            filter_mediator,
+        )))
+    }
+
+    fn parse_switch_mediator(&mut self) -> Result<ast::AstNode> {
+        let mut switch_mediator = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { attributes, .. }) => ast::SwitchMediator {
+                source: attribute(attributes, "source").context("switch without source")?,
+                cases: Vec::new(),
+                default: None,
+            },
+            _ => {
+                bail!("not a switch mediator");
+            }
+        };
+
+        self.next_event();
+        while !self.at_end_of("switch") {
+            match self.current_event.as_ref() {
+                Some(XmlEvent::StartElement {
+                    name, attributes, ..
+                }) if name.local_name == "case" => {
+                    let regex = attribute(attributes, "regex").context("case without regex")?;
+                    self.next_event();
+                    switch_mediator.cases.push(ast::SwitchCase {
+                        regex,
+                        mediators: self.parse_mediators_until("case")?,
+                    });
+                }
+                Some(XmlEvent::StartElement { name, .. }) if name.local_name == "default" => {
+                    self.next_event();
+                    switch_mediator.default = Some(self.parse_mediators_until("default")?);
+                }
+                Some(XmlEvent::StartElement { name, .. }) => {
+                    bail!(
+                        "not a supported switch element: element {}",
+                        name.local_name
+                    );
+                }
+                _ => {
+                    bail!("error parsing switch mediator");
+                }
+            }
+        }
+
+        self.next_event();
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Switch(
+            switch_mediator,
+        )))
+    }
+
+    fn parse_payload_factory_mediator(&mut self) -> Result<ast::AstNode> {
+        //the inline format is the payload template, its whitespace is kept
+        let element = self.parse_verbatim_element()?;
+        let mut format = None;
+        let mut args = Vec::new();
+
+        for child in element.elements() {
+            match child.name.as_str() {
+                "format" => {
+                    format = Some(match child.attribute("key") {
+                        Some(key) => ast::PayloadFormat::Key(key.to_string()),
+                        None => ast::PayloadFormat::Inline(child.children.clone()),
+                    });
+                }
+                "args" => {
+                    for arg in child.elements() {
+                        if arg.name != "arg" {
+                            bail!("not a supported args element: element {}", arg.name);
+                        }
+                        args.push(parse_payload_arg(arg)?);
+                    }
+                }
+                _ => {
+                    bail!(
+                        "not a supported payloadFactory element: element {}",
+                        child.name
+                    );
+                }
+            }
+        }
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::PayloadFactory(
+            ast::PayloadFactoryMediator {
+                media_type: element.attribute("media-type").map(String::from),
+                template_type: element.attribute("template-type").map(String::from),
+                format: format.context("payloadFactory without format")?,
+                args,
+            },
+        )))
+    }
+
+    fn parse_header_mediator(&mut self) -> Result<ast::AstNode> {
+        let element = self.parse_raw_element()?;
+        let inline = element.elements().next().cloned();
+
+        let value = match (
+            element.attribute("value"),
+            element.attribute("expression"),
+            inline,
+        ) {
+            (Some(value), None, None) => Some(ast::HeaderValue::Value(value.to_string())),
+            (None, Some(expression), None) => {
+                Some(ast::HeaderValue::Expression(expression.to_string()))
+            }
+            (None, None, Some(inline)) => Some(ast::HeaderValue::Inline(inline)),
+            (None, None, None) => None,
+            _ => {
+                bail!("header mediator needs only one of value, expression or an inline header");
+            }
+        };
+
+        let header_mediator = ast::HeaderMediator {
+            name: element.attribute("name").map(String::from),
+            action: parse_attribute(&element, "action")?,
+            scope: parse_attribute(&element, "scope")?,
+            value,
+        };
+
+        if header_mediator.name.is_none()
+            && !matches!(header_mediator.value, Some(ast::HeaderValue::Inline(_)))
+        {
+            bail!("header without name");
+        }
+        if header_mediator.value.is_none()
+            && header_mediator.action != Some(ast::HeaderAction::Remove)
+        {
+            bail!(
+                "header {} without value",
+                header_mediator.name.unwrap_or_default()
+            );
+        }
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Header(
+            header_mediator,
+        )))
+    }
+
+    fn parse_send_mediator(&mut self) -> Result<ast::AstNode> {
+        let mut send_mediator = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { attributes, .. }) => ast::SendMediator {
+                receive: attribute(attributes, "receive"),
+                build_message: parse_attribute(attributes, "buildmessage")?,
+                endpoint: None,
+            },
+            _ => {
+                bail!("not a send mediator");
+            }
+        };
+
+        self.next_event();
+        while !self.at_end_of("send") {
+            match self.current_event.as_ref() {
+                Some(XmlEvent::StartElement { name, .. })
+                    if name.local_name == "endpoint" && send_mediator.endpoint.is_none() =>
+                {
+                    send_mediator.endpoint = Some(self.parse_endpoint_ref()?);
+                }
+                _ => {
+                    bail!("error parsing send mediator");
+                }
+            }
+        }
+
+        self.next_event();
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Send(send_mediator)))
+    }
+
+    fn parse_callout_mediator(&mut self) -> Result<ast::AstNode> {
+        let scope = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { namespace, .. }) => namespace.clone(),
+            _ => {
+                bail!("not a callout mediator");
+            }
+        };
+        let element = self.parse_raw_element()?;
+        let mut callout_mediator = ast::CalloutMediator {
+            service_url: element.attribute("serviceURL").map(String::from),
+            endpoint_key: element.attribute("endpointKey").map(String::from),
+            action: element.attribute("action").map(String::from),
+            init_axis2_client_options: parse_attribute(&element, "initAxis2ClientOptions")?,
+            configuration: None,
+            source: None,
+            target: None,
+            enable_sec: None,
+        };
+
+        if callout_mediator.service_url.is_none() && callout_mediator.endpoint_key.is_none() {
+            bail!("callout mediator without serviceURL or endpointKey");
+        }
+
+        for child in element.elements() {
+            match child.name.as_str() {
+                "configuration" => {
+                    callout_mediator.configuration = Some(ast::CalloutConfiguration {
+                        axis2xml: child.attribute("axis2xml").map(String::from),
+                        repository: child.attribute("repository").map(String::from),
+                    });
+                }
+                "source" => {
+                    callout_mediator.source = Some(ast::CalloutSource {
+                        xpath: child.attribute("xpath").map(String::from),
+                        key: child.attribute("key").map(String::from),
+                        kind: child.attribute("type").map(String::from),
+                        namespaces: used_namespaces(
+                            &scope,
+                            child,
+                            child.attribute("xpath").unwrap_or_default(),
+                        ),
+                    });
+                }
+                "target" => {
+                    callout_mediator.target = Some(ast::CalloutTarget {
+                        xpath: child.attribute("xpath").map(String::from),
+                        key: child.attribute("key").map(String::from),
+                        namespaces: used_namespaces(
+                            &scope,
+                            child,
+                            child.attribute("xpath").unwrap_or_default(),
+                        ),
+                    });
+                }
+                "enableSec" => {
+                    callout_mediator.enable_sec = Some(ast::EnableSec {
+                        policy: child.attribute("policy").map(String::from),
+                        outbound_policy: child.attribute("outboundPolicy").map(String::from),
+                        inbound_policy: child.attribute("inboundPolicy").map(String::from),
+                    });
+                }
+                _ => {
+                    bail!("not a supported callout element: element {}", child.name);
+                }
+            }
+        }
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Callout(Box::new(
+            callout_mediator,
+        ))))
+    }
+
+    fn parse_store_mediator(&mut self) -> Result<ast::AstNode> {
+        let element = self.parse_raw_element()?;
+        let store_mediator = ast::StoreMediator {
+            message_store: element
+                .attribute("messageStore")
+                .context("store mediator without messageStore")?
+                .to_string(),
+            sequence: element.attribute("sequence").map(String::from),
+        };
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Store(
+            store_mediator,
+        )))
+    }
+
+    fn parse_makefault_mediator(&mut self) -> Result<ast::AstNode> {
+        let scope = match self.current_event.as_ref() {
+            Some(XmlEvent::StartElement { namespace, .. }) => namespace.clone(),
+            _ => {
+                bail!("not a makefault mediator");
+            }
+        };
+        let element = self.parse_raw_element()?;
+        let mut namespaces: Vec<(String, String)> = Vec::new();
+        let mut code = None;
+        let mut reason = None;
+        let mut node = None;
+        let mut role = None;
+        let mut detail = None;
+
+        for child in element.elements() {
+            let value = child
+                .attribute("value")
+                .or(child.attribute("expression"))
+                .unwrap_or_default();
+            for (prefix, uri) in used_namespaces(&scope, child, value) {
+                if !namespaces.iter().any(|(known, _)| *known == prefix) {
+                    namespaces.push((prefix, uri));
+                }
+            }
+
+            match child.name.as_str() {
+                "code" => code = Some(parse_fault_value(child)?),
+                "reason" => reason = Some(parse_fault_value(child)?),
+                "node" => node = Some(child.text()),
+                "role" => role = Some(child.text()),
+                "detail" => {
+                    detail = Some(ast::FaultDetail {
+                        expression: child.attribute("expression").map(String::from),
+                        inline: child.children.clone(),
+                    });
+                }
+                _ => {
+                    bail!("not a supported makefault element: element {}", child.name);
+                }
+            }
+        }
+
+        let makefault_mediator = ast::MakefaultMediator {
+            namespaces,
+            version: parse_attribute(&element, "version")?,
+            response: parse_attribute(&element, "response")?,
+            code,
+            reason: reason.context("makefault without reason")?,
+            node,
+            role,
+            detail,
+        };
+
+        if makefault_mediator.code.is_none()
+            && makefault_mediator.version != Some(ast::FaultVersion::Pox)
+        {
+            bail!("soap fault without code");
+        }
+
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Makefault(
+            makefault_mediator,
+        )))
+    }
+
+    //--------------------------------------------------------------------------------//
+
+    fn parse_named_endpoint(&mut self) -> Result<ast::AstNode> {
+        let element = self.parse_raw_element()?;
+        let mut endpoint = parse_endpoint(&element).context("error parsing endpoint")?;
+        if endpoint.name.is_none() {
+            bail!("endpoint without name");
+        }
+        endpoint.xmlns = element.namespace;
+
+        Result::Ok(ast::AstNode::Endpoint(endpoint))
+    }
+
+    fn parse_endpoint_ref(&mut self) -> Result<ast::EndpointRef> {
+        parse_endpoint_ref(&self.parse_raw_element()?)
+    }
+
+    //--------------------------------------------------------------------------------//
+
+    fn parse_property(&mut self) -> Result<ast::AstNode> {
+        let element = self.parse_raw_element()?;
+        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Property(
+            parse_raw_property(&element)?,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::endpoint;
+    use crate::{ast, parse_documents, parse_fragment, Fragment, FragmentContext, Parser};
+
+    #[test]
+    fn test_in_sequence() {
+        let input = r#"
+        <inSequence>
+            <log level="custom">
+                <property name="/validate" value="inSequence" />
+            </log>
+            <log level="full" />
+      </inSequence>
+        "#;
+
+        let mut parser = Parser::new(input.as_bytes());
+        let program = parser.parse_progarm();
+
+        println!("{:?}", program);
+
+        assert!(program.is_ok());
+
+        let program = program.unwrap();
+
+        println!("{:?}", program);
+
+        assert_eq!(program.ast_nodes.len(), 1);
+    }
+
+    #[test]
+    fn test_property_mediator() {
+        for input in [
+            r#"<property name="COUNT" expression="count(//a) &lt; 2"/>"#,
+            r#"<property name="a&amp;b" value="&quot;quoted&quot;"/>"#,
+            r#"<property name="HTTP_SC" value="404" scope="axis2"/>"#,
+            r#"<property name="TEMP" action="remove" scope="axis2"/>"#,
+            r#"<property name="ID" expression="//id" type="INTEGER" pattern="(\d+)-.*" group="1"/>"#,
+            r#"<property name="PAYLOAD" scope="default" type="OM"><m:order xmlns:m="urn:orders"><m:id>1</m:id></m:order></property>"#,
+        ] {
+            match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+                Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), input),
+                _ => {
+                    panic!("not a mediator");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_om_property_before_sibling() {
+        let input = r#"
+        <property name="PAYLOAD" type="OM">
+            <m:order xmlns:m="urn:orders"><m:id>1</m:id></m:order>
+        </property>
+        <log level="full" />
+        "#;
+
+        let mediators = match parse_fragment(input, FragmentContext::Mediators).unwrap() {
+            Fragment::Mediators(mediators) => mediators,
+            _ => {
+                panic!("not a mediator list");
+            }
+        };
+
+        match &mediators[..] {
+            [ast::Mediators::Property(property), ast::Mediators::Log(_)] => {
+                assert_eq!(property.kind.as_deref(), Some("OM"));
+                assert_eq!(property.inline.len(), 1);
+            }
+            _ => panic!("unexpected mediators: {:?}", mediators),
+        }
+    }
+
+    #[test]
+    fn test_log_mediator() {
+        let input = r#"
+        <inSequence>
+            <log level="custom">
+                <property name="/validate" value="inSequence" />
+            </log>
+            <log level="full" />
+            <log level="debug">
+                <property name="/validate" value="foobar" />
+            </log>
+        </inSequence>
+        "#;
+
+        let mut parser = Parser::new(input.as_bytes());
+        let program = parser.parse_progarm();
+
+        println!("{:?}", program);
+
+        assert!(program.is_ok());
+
+        let program = program.unwrap();
+
+        println!("{:?}", program);
+
+        assert_eq!(program.ast_nodes.len(), 1);
+
+        for ast_node in program.ast_nodes {
+            match ast_node {
+                ast::AstNode::Sequence(ast::Sequences::InSequence(in_sequence)) => {
+                    assert_eq!(in_sequence.mediators.len(), 3);
+                    match &in_sequence.mediators[0] {
+                        ast::Mediators::Log(log_mediator) => {
+                            assert_eq!(log_mediator.level, "custom");
+                            assert_eq!(log_mediator.properties.len(), 1);
+                            assert_eq!(log_mediator.properties[0].name, "/validate");
+                            assert_eq!(log_mediator.properties[0].value, "inSequence");
+                        }
+                        _ => {
+                            panic!("not a log mediator");
+                        }
+                    }
+                    match &in_sequence.mediators[1] {
+                        ast::Mediators::Log(log_mediator) => {
+                            assert_eq!(log_mediator.level, "full");
+                            assert_eq!(log_mediator.properties.len(), 0);
+                        }
+                        _ => {
+                            panic!("not a log mediator");
+                        }
+                    }
+                    match &in_sequence.mediators[2] {
+                        ast::Mediators::Log(log_mediator) => {
+                            assert_eq!(log_mediator.level, "debug");
+                            assert_eq!(log_mediator.properties.len(), 1);
+                            assert_eq!(log_mediator.properties[0].name, "/validate");
+                            assert_eq!(log_mediator.properties[0].value, "foobar");
+                        }
+                        _ => {
+                            panic!("not a log mediator");
+                        }
+                    }
+                }
+                _ => {
+                    panic!("not a in sequence");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_api() {
+        let input = r#"<?xml version="1.0" encoding="uTF-8"?>
+        <api context="/validate" name="validate_xfcc" xmlns="http://ws.apache.org/ns/synapse" trace="enable" statistics="disable" version="1.0.0" version-type="url">
+        </api>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+
+        assert_eq!(program.ast_nodes.len(), 1);
+        match &program.ast_nodes[0] {
+            ast::AstNode::Api(api) => {
+                assert_eq!(api.context, "/validate");
+                assert_eq!(api.name, "validate_xfcc");
+                assert_eq!(
+                    api.xmlns.as_deref(),
+                    Some("http://ws.apache.org/ns/synapse")
+                );
+                assert_eq!(api.trace, Some(ast::Toggle::Enable));
+                assert_eq!(api.statistics, Some(ast::Toggle::Disable));
+                assert_eq!(api.version.as_deref(), Some("1.0.0"));
+                assert_eq!(api.version_type, Some(ast::VersionType::Url));
+            }
+            _ => {
+                panic!("not an api");
+            }
+        }
+
+        //handlers follow the resources
+        let input = r#"<api context="/orders" name="orders"><resource methods="GET"></resource><handlers><handler class="org.acme.AuthHandler"><property name="realm" value="orders"/></handler><handler class="org.acme.Throttle"/></handlers></api>"#;
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        assert_eq!(program.to_string(), input);
+
+        //attribute values are escaped when serialized
+        let input = r#"<api context="/a&amp;b" name="&quot;orders&quot;" version="1&lt;2"></api>"#;
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        assert_eq!(program.to_string(), input);
+
+        let input = r#"<api context="/validate" name="validate_xfcc" trace="on"></api>"#;
+        assert!(Parser::new(input.as_bytes()).parse_progarm().is_err());
+
+        let input = r#"<api name="validate_xfcc"></api>"#;
+        assert!(Parser::new(input.as_bytes()).parse_progarm().is_err());
+    }
+
+    #[test]
+    fn test_resource() {
+        let input = r#"
+        <api context="/validate" name="validate_xfcc" xmlns="http://ws.apache.org/ns/synapse">
+            <resource methods="GET POST" uri-template="/{id}">
+                <inSequence>
+                    <log level="custom">
+                        <property name="/validate" value="inSequence" />
+                    </log>
+                </inSequence>
+                <outSequence>
+                    <log level="full" />
+                </outSequence>
+                <faultSequence />
+            </resource>
+            <resource methods="DELETE" url-mapping="/*" />
+        </api>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+
+        match &program.ast_nodes[0] {
+            ast::AstNode::Api(api) => {
+                assert_eq!(api.resources.len(), 2);
+
+                let resource = &api.resources[0];
+                assert_eq!(
+                    resource.methods,
+                    vec![ast::HttpMethod::Get, ast::HttpMethod::Post]
+                );
+                assert_eq!(resource.uri_template.as_deref(), Some("/{id}"));
+                assert_eq!(resource.in_sequence().unwrap().mediators.len(), 1);
+                assert_eq!(resource.out_sequence().unwrap().mediators.len(), 1);
+                assert!(resource.fault_sequence().unwrap().mediators.is_empty());
+
+                let resource = &api.resources[1];
+                assert_eq!(resource.methods, vec![ast::HttpMethod::Delete]);
+                assert_eq!(resource.url_mapping.as_deref(), Some("/*"));
+                assert!(resource.sequences.is_empty());
+            }
+            _ => {
+                panic!("not an api");
+            }
+        }
+
+        let path: ast::NodePath = "api[validate_xfcc]/resource[0]/outSequence/mediator[0]"
+            .parse()
+            .unwrap();
+        assert!(matches!(
+            program.find(&path),
+            Some(ast::NodeRef::Mediator(ast::Mediators::Log(_)))
+        ));
+
+        //query string templates are escaped when serialized
+        let input = r#"<api context="/orders" name="orders"><resource methods="GET" uri-template="/orders?a={a}&amp;b={b}"></resource><resource methods="GET" url-mapping="/search?q=&lt;all&gt;"></resource></api>"#;
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        assert_eq!(program.to_string(), input);
+
+        let input = r#"<api context="/" name="a"><resource methods="FETCH" /></api>"#;
+        assert!(Parser::new(input.as_bytes()).parse_progarm().is_err());
+    }
+
+    #[test]
+    fn test_proxy() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <proxy xmlns="http://ws.apache.org/ns/synapse" name="StockQuoteProxy" transports="http https" startOnLoad="true" trace="disable">
+            <description>stock quotes</description>
+            <target faultSequence="errorHandler">
+                <inSequence>
+                    <log level="full" />
+                </inSequence>
+                <endpoint>
+                    <http method="post" uri-template="http://localhost:9000/services/SimpleStockQuoteService" />
+                </endpoint>
+            </target>
+            <publishWSDL preservePolicy="true">
+                <wsdl:definitions xmlns:wsdl="http://schemas.xmlsoap.org/wsdl/" name="StockQuote">
+                    <wsdl:types />
+                </wsdl:definitions>
+                <resource location="stockquote.xsd" key="conf:/stockquote.xsd" />
+            </publishWSDL>
+            <parameter name="serviceType">proxy</parameter>
+        </proxy>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+
+        match &program.ast_nodes[0] {
+            ast::AstNode::Proxy(proxy) => {
+                assert_eq!(proxy.name, "StockQuoteProxy");
+                assert_eq!(proxy.transports, vec!["http", "https"]);
+                assert_eq!(proxy.start_on_load, Some(true));
+                assert_eq!(proxy.trace, Some(ast::Toggle::Disable));
+                assert_eq!(proxy.description.as_deref(), Some("stock quotes"));
+
+                let target = proxy.target.as_ref().unwrap();
+                assert_eq!(target.fault_sequence.as_deref(), Some("errorHandler"));
+                assert_eq!(target.sequences.len(), 1);
+                assert!(matches!(target.endpoint, Some(ast::EndpointRef::Inline(_))));
+
+                let publish_wsdl = proxy.publish_wsdl.as_ref().unwrap();
+                assert_eq!(publish_wsdl.preserve_policy, Some(true));
+                let definition = publish_wsdl.definition.as_ref().unwrap();
+                assert_eq!(definition.prefix.as_deref(), Some("wsdl"));
+                assert_eq!(definition.name, "definitions");
+                assert_eq!(
+                    publish_wsdl.resources[0].key.as_deref(),
+                    Some("conf:/stockquote.xsd")
+                );
+
+                assert_eq!(proxy.parameters[0].name, "serviceType");
+                assert_eq!(proxy.parameters[0].value, "proxy");
+            }
+            _ => {
+                panic!("not a proxy");
+            }
+        }
+
+        let path: ast::NodePath = "proxy[StockQuoteProxy]/target/inSequence/mediator[0]"
+            .parse()
+            .unwrap();
+        assert!(program.find(&path).is_some());
+
+        //attribute values and parameter names are escaped when serialized
+        let input = r#"<proxy name="a&amp;b"><target inSequence="in&amp;put" endpoint="ep&quot;1"></target><publishWSDL uri="http://wsdl?svc=a&amp;v=2"><resource location="a&amp;b.xsd"/></publishWSDL><parameter name="p&amp;q">jdbc:x?a=1&amp;b=2</parameter></proxy>"#;
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        assert_eq!(program.to_string(), input);
+
+        let input = r#"<proxy name="p" startOnLoad="yes"></proxy>"#;
+        assert!(Parser::new(input.as_bytes()).parse_progarm().is_err());
+    }
+
+    #[test]
+    fn test_out_and_fault_sequence() {
+        let input = r#"
+        <inSequence>
+            <log level="full" />
+        </inSequence>
+        <outSequence>
+            <log level="custom">
+                <property name="/health" value="outSequence" />
+            </log>
+        </outSequence>
+        <faultSequence>
+            <log level="custom">
+                <property name="ERROR_MESSAGE" expression="$ctx:ERROR_MESSAGE" />
+            </log>
+        </faultSequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+
+        assert_eq!(program.ast_nodes.len(), 3);
+        assert!(matches!(
+            program.ast_nodes[1],
+            ast::AstNode::Sequence(ast::Sequences::OutSequence(_))
+        ));
+        assert!(matches!(
+            program.ast_nodes[2],
+            ast::AstNode::Sequence(ast::Sequences::FaultSequence(_))
+        ));
+
+        //serialized program parses to the same program
+        let output = program.to_string();
+        let reparsed = Parser::new(output.as_bytes()).parse_progarm().unwrap();
+        assert_eq!(reparsed.to_string(), output);
+    }
+
+    #[test]
+    fn test_call_mediator() {
+        let input = r#"
+        <inSequence>
+            <call>
+                <endpoint>
+                    <http method="GET" uri-template="http://httpbin:80/get" />
+                </endpoint>
+            </call>
+            <call blocking="true">
+                <endpoint key="HttpbinEndpoint" />
+            </call>
+            <call />
+        </inSequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        let mediators = program.mediators();
+        assert_eq!(mediators.len(), 3);
+
+        match mediators[0] {
+            ast::Mediators::Call(call_mediator) => {
+                assert_eq!(call_mediator.blocking, None);
+                assert!(matches!(
+                    call_mediator.endpoint,
+                    Some(ast::EndpointRef::Inline(_))
+                ));
+            }
+            _ => {
+                panic!("not a call mediator");
+            }
+        }
+        match mediators[1] {
+            ast::Mediators::Call(call_mediator) => {
+                assert_eq!(call_mediator.blocking, Some(true));
+                assert!(matches!(
+                    &call_mediator.endpoint,
+                    Some(ast::EndpointRef::Key(key)) if key == "HttpbinEndpoint"
+                ));
+            }
+            _ => {
+                panic!("not a call mediator");
+            }
+        }
+        match mediators[2] {
+            ast::Mediators::Call(call_mediator) => assert!(call_mediator.endpoint.is_none()),
+            _ => {
+                panic!("not a call mediator");
+            }
+        }
+
+        //a call mediator has at most one endpoint
+        let input = r#"
+        <call>
+            <endpoint key="HttpbinEndpoint" />
+            <endpoint key="OtherEndpoint" />
+        </call>
+        "#;
+        assert!(parse_fragment(input, FragmentContext::Mediator).is_err());
+    }
+
+    #[test]
+    fn test_filter_mediator() {
+        let input = r#"
+        <inSequence>
+            <filter source="get-property('To')" regex=".*/orders.*">
+                <then>
+                    <filter xpath="$ctx:ORDER_ID &gt; 10">
+                        <drop/>
+                    </filter>
+                </then>
+                <else>
+                    <log level="full" />
+                </else>
+            </filter>
+        </inSequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        let paths: Vec<String> = program
+            .walk()
+            .iter()
+            .map(|(path, _)| path.to_string())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "inSequence",
+                "inSequence/mediator[0]",
+                "inSequence/mediator[0]/then/mediator[0]",
+                "inSequence/mediator[0]/then/mediator[0]/then/mediator[0]",
+                "inSequence/mediator[0]/else/mediator[0]"
+            ]
+        );
+
+        let path: ast::NodePath = "inSequence/mediator[0]/then/mediator[0]".parse().unwrap();
+        match program.find(&path) {
+            Some(ast::NodeRef::Mediator(ast::Mediators::Filter(filter_mediator))) => {
+                assert!(matches!(
+                    &filter_mediator.condition,
+                    ast::FilterCondition::Xpath(xpath) if xpath == "$ctx:ORDER_ID > 10"
+                ));
+                assert!(filter_mediator.else_mediators.is_none());
+            }
+            _ => {
+                panic!("not a filter mediator");
+            }
+        }
+
+        //serialized filter parses to the same filter
+        let output = program.mediators()[0].to_string();
+        match parse_fragment(&output, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), output),
+            _ => {
+                panic!("not a mediator");
+            }
+        }
+
+        let input = r#"<filter xpath="true()" source="foo"><drop/></filter>"#;
+        assert!(parse_fragment(input, FragmentContext::Mediator).is_err());
+    }
+
+    #[test]
+    fn test_switch_mediator() {
+        let input = r#"
+        <switch source="json-eval($.type)">
+            <case regex="order">
+                <property name="TYPE" value="order" />
+                <respond/>
+            </case>
+            <case regex="refund|return">
+                <drop/>
+            </case>
+            <default>
+                <log level="full" />
+            </default>
+        </switch>
+        "#;
+
+        let switch_mediator = match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(ast::Mediators::Switch(switch_mediator)) => switch_mediator,
+            _ => {
+                panic!("not a switch mediator");
+            }
+        };
+
+        assert_eq!(switch_mediator.source, "json-eval($.type)");
+        assert_eq!(switch_mediator.cases.len(), 2);
+        assert_eq!(switch_mediator.cases[1].regex, "refund|return");
+        assert_eq!(switch_mediator.cases[0].mediators.len(), 2);
+        assert_eq!(switch_mediator.default.as_ref().unwrap().len(), 1);
+
+        //serialized switch parses to the same switch
+        let output = switch_mediator.to_string();
+        match parse_fragment(&output, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), output),
+            _ => {
+                panic!("not a mediator");
+            }
+        }
+
+        let input = r#"<switch source="foo"><case><drop/></case></switch>"#;
+        assert!(parse_fragment(input, FragmentContext::Mediator).is_err());
+    }
+
+    #[test]
+    fn test_payload_factory_mediator() {
+        let input = r#"
+        <payloadFactory media-type="xml">
+            <format>
+                <m:getQuote xmlns:m="http://services.samples">
+                    <m:request><m:symbol>$1</m:symbol></m:request>
+                </m:getQuote>
+            </format>
+            <args>
+                <arg evaluator="xml" expression="//symbol/text()" />
+                <arg value="IBM" />
+            </args>
+        </payloadFactory>
+        "#;
+
+        let payload_factory = match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(ast::Mediators::PayloadFactory(payload_factory)) => payload_factory,
+            _ => {
+                panic!("not a payloadFactory mediator");
+            }
+        };
+
+        assert_eq!(payload_factory.media_type.as_deref(), Some("xml"));
+        match &payload_factory.format {
+            ast::PayloadFormat::Inline(content) => assert_eq!(
+                content[1].to_string(),
+                r#"<m:getQuote xmlns:m="http://services.samples">
+                    <m:request><m:symbol>$1</m:symbol></m:request>
+                </m:getQuote>"#
+            ),
+            _ => {
+                panic!("not an inline format");
+            }
+        }
+        assert!(matches!(
+            &payload_factory.args[..],
+            [
+                ast::PayloadArg::Expression { evaluator: Some(evaluator), .. },
+                ast::PayloadArg::Value(value)
+            ] if evaluator == "xml" && value == "IBM"
+        ));
+
+        let input = r#"
+        <payloadFactory media-type="json">
+            <format>{"symbol": "$1", "price": 1 &lt; 2}</format>
+            <args><arg evaluator="json" expression="$.symbol" /></args>
+        </payloadFactory>
+        "#;
+
+        let output = match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(mediator) => mediator.to_string(),
+            _ => {
+                panic!("not a mediator");
+            }
+        };
+        assert_eq!(
+            output,
+            r#"<payloadFactory media-type="json"><format>{"symbol": "$1", "price": 1 &lt; 2}</format><args><arg evaluator="json" expression="$.symbol"/></args></payloadFactory>"#
+        );
+
+        //the format is kept as written, including indentation and cdata sections
+        for input in [
+            "<payloadFactory media-type=\"json\"><format>\n    {\n        \"symbol\": \"$1\"\n    }\n</format><args><arg evaluator=\"json\" expression=\"$.symbol\"/></args></payloadFactory>",
+            "<payloadFactory media-type=\"json\"><format><![CDATA[{\"price\": 1 < 2}]]></format><args></args></payloadFactory>",
+        ] {
+            match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+                Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), input),
+                _ => {
+                    panic!("not a mediator");
+                }
+            }
+        }
+
+        //attribute values are escaped when serialized
+        let input = r#"<payloadFactory media-type="json&amp;xml" template-type="a&quot;b"><format key="conf:/f?a=1&amp;b=2"/><args><arg evaluator="j&amp;s" expression="$.a"/></args></payloadFactory>"#;
+        match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), input),
+            _ => {
+                panic!("not a mediator");
+            }
+        }
+
+        let input = r#"<payloadFactory><args><arg value="1" /></args></payloadFactory>"#;
+        assert!(parse_fragment(input, FragmentContext::Mediator).is_err());
+    }
+
+    #[test]
+    fn test_header_mediator() {
+        let input = r#"
+            <header name="Authorization" expression="fn:concat('Bearer ', $ctx:TOKEN)" scope="transport"/>
+            <header name="X-Debug" action="remove" scope="transport"/>
+            <header>
+                <t:Trace xmlns:t="http://example.com/trace">on</t:Trace>
+            </header>
+        "#;
+
+        let mediators = match parse_fragment(input, FragmentContext::Mediators).unwrap() {
+            Fragment::Mediators(mediators) => mediators,
+            _ => {
+                panic!("not a mediator list");
+            }
+        };
+
+        match &mediators[..] {
+            [ast::Mediators::Header(authorization), ast::Mediators::Header(debug), ast::Mediators::Header(trace)] =>
+            {
+                assert_eq!(authorization.scope, Some(ast::Scope::Transport));
+                assert!(matches!(
+                    &authorization.value,
+                    Some(ast::HeaderValue::Expression(expression)) if expression.starts_with("fn:concat")
+                ));
+                assert_eq!(debug.action, Some(ast::HeaderAction::Remove));
+                assert!(debug.value.is_none());
+                assert!(trace.name.is_none());
+                assert!(matches!(
+                    &trace.value,
+                    Some(ast::HeaderValue::Inline(element)) if element.name == "Trace"
+                ));
+            }
+            _ => {
+                panic!("not three header mediators");
+            }
+        }
+
+        assert_eq!(
+            mediators[1].to_string(),
+            r#"<header name="X-Debug" action="remove" scope="transport"/>"#
+        );
+
+        assert!(parse_fragment(r#"<header name="foo"/>"#, FragmentContext::Mediator).is_err());
+        assert!(parse_fragment(
+            r#"<header name="foo" value="bar" scope="message"/>"#,
+            FragmentContext::Mediator
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_class_mediator() {
+        let input = r#"
+            <class name="ch.integon.XfccMediator"/>
+            <class name="com.example.RateLimiter">
+                <property name="limit" value="100"/>
+                <property name="window" value="60"/>
+            </class>
+        "#;
+
+        let mediators = match parse_fragment(input, FragmentContext::Mediators).unwrap() {
+            Fragment::Mediators(mediators) => mediators,
+            _ => {
+                panic!("not a mediator list");
+            }
+        };
+
+        match &mediators[..] {
+            [ast::Mediators::Class(xfcc), ast::Mediators::Class(rate_limiter)] => {
+                assert_eq!(xfcc.name, "ch.integon.XfccMediator");
+                assert!(xfcc.properties.is_empty());
+                assert_eq!(rate_limiter.properties.len(), 2);
+                assert_eq!(rate_limiter.properties[1].name, "window");
+                assert_eq!(rate_limiter.properties[1].value, "60");
+            }
+            _ => {
+                panic!("not two class mediators");
+            }
+        }
+
+        assert_eq!(
+            mediators[0].to_string(),
+            r#"<class name="ch.integon.XfccMediator"/>"#
+        );
+        //the class name is escaped when serialized
+        let input = r#"<class name="a&amp;b"/>"#;
+        match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), input),
+            _ => {
+                panic!("not a mediator");
+            }
+        }
+
+        assert!(parse_fragment("<class/>", FragmentContext::Mediator).is_err());
+    }
+
+    #[test]
+    fn test_named_sequence() {
+        let input = r#"
+        <sequence xmlns="http://ws.apache.org/ns/synapse" name="auth" onError="authFault" trace="disable">
+            <class name="ch.integon.XfccMediator"/>
+            <sequence key="logRequest"/>
+        </sequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        let paths: Vec<String> = program
+            .walk()
+            .iter()
+            .map(|(path, _)| path.to_string())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "sequence[auth]",
+                "sequence[auth]/mediator[0]",
+                "sequence[auth]/mediator[1]"
+            ]
+        );
+
+        match &program.ast_nodes[0] {
+            ast::AstNode::Sequence(ast::Sequences::NamedSequence(named_sequence)) => {
+                assert_eq!(named_sequence.on_error.as_deref(), Some("authFault"));
+                assert_eq!(named_sequence.trace, Some(ast::Toggle::Disable));
+                assert!(matches!(
+                    &named_sequence.mediators[1],
+                    ast::Mediators::Sequence(sequence_mediator) if sequence_mediator.key == "logRequest"
+                ));
+            }
+            _ => {
+                panic!("not a named sequence");
+            }
+        }
+
+        //serialized sequence parses to the same sequence
+        let output = program.ast_nodes[0].to_string();
+        let program = Parser::new(output.as_bytes()).parse_progarm().unwrap();
+        assert_eq!(program.ast_nodes[0].to_string(), output);
+
+        //attribute values are escaped when serialized
+        let input = r#"<sequence name="a&amp;b" onError="fault&quot;1"><sequence key="conf:/seq?a=1&amp;b=2"/></sequence>"#;
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        assert_eq!(program.to_string(), input);
+
+        assert!(parse_fragment("<sequence/>", FragmentContext::Mediator).is_err());
+    }
+
+    #[test]
+    fn test_enrich_mediator() {
+        let input = r#"
+            <enrich>
+                <source clone="true" type="custom" xpath="$body//order"/>
+                <target type="property" property="ORDER"/>
+            </enrich>
+            <enrich>
+                <source type="inline" clone="true">{"status": "accepted"}</source>
+                <target action="child" xpath="json-eval($.result)"/>
+            </enrich>
+        "#;
+
+        let mediators = match parse_fragment(input, FragmentContext::Mediators).unwrap() {
+            Fragment::Mediators(mediators) => mediators,
+            _ => {
+                panic!("not a mediator list");
+            }
+        };
+
+        match &mediators[..] {
+            [ast::Mediators::Enrich(to_property), ast::Mediators::Enrich(inline)] => {
+                assert_eq!(to_property.source.clone, Some(true));
+                assert_eq!(to_property.source.kind, Some(ast::EnrichType::Custom));
+                assert_eq!(to_property.source.xpath.as_deref(), Some("$body//order"));
+                assert_eq!(to_property.target.kind, Some(ast::EnrichType::Property));
+                assert_eq!(to_property.target.property.as_deref(), Some("ORDER"));
+                assert_eq!(inline.source.kind, Some(ast::EnrichType::Inline));
+                assert_eq!(inline.target.action, Some(ast::EnrichAction::Child));
+            }
+            _ => {
+                panic!("not two enrich mediators");
+            }
+        }
+
+        assert_eq!(
+            mediators[1].to_string(),
+            r#"<enrich><source clone="true" type="inline">{"status": "accepted"}</source><target action="child" xpath="json-eval($.result)"/></enrich>"#
+        );
+
+        //property names and keys are escaped when serialized
+        let input = r#"<enrich><source type="property" property="a&amp;b"/><target type="property" property="c&quot;d"/></enrich>"#;
+        match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), input),
+            _ => {
+                panic!("not a mediator");
+            }
+        }
+
+        let input = r#"<enrich><source type="payload"/><target type="body"/></enrich>"#;
+        assert!(parse_fragment(input, FragmentContext::Mediator).is_err());
+        let input = r#"<enrich><source type="body"/></enrich>"#;
+        assert!(parse_fragment(input, FragmentContext::Mediator).is_err());
+    }
+
+    #[test]
+    fn test_xslt_and_xquery_mediators() {
+        let input = r#"
+            <xslt key="conf:/xslt/order.xslt" source="//order">
+                <property name="currency" value="CHF"/>
+                <property name="customer" expression="$ctx:CUSTOMER"/>
+                <feature name="http://ws.apache.org/ns/synapse/transform/feature/dom" value="true"/>
+            </xslt>
+            <xquery key="conf:/xquery/total.xq" target="//total">
+                <variable name="payload" type="ELEMENT"/>
+                <variable name="rate" type="DOUBLE" expression="$ctx:RATE"/>
+            </xquery>
+        "#;
+
+        let mediators = match parse_fragment(input, FragmentContext::Mediators).unwrap() {
+            Fragment::Mediators(mediators) => mediators,
+            _ => {
+                panic!("not a mediator list");
+            }
+        };
+
+        match &mediators[..] {
+            [ast::Mediators::Xslt(xslt_mediator), ast::Mediators::Xquery(xquery_mediator)] => {
+                assert_eq!(xslt_mediator.key, "conf:/xslt/order.xslt");
+                assert_eq!(xslt_mediator.source.as_deref(), Some("//order"));
+                assert_eq!(xslt_mediator.properties.len(), 2);
+                assert!(xslt_mediator.features[0].value);
+                assert_eq!(xquery_mediator.target.as_deref(), Some("//total"));
+                assert_eq!(xquery_mediator.variables[1].kind, "DOUBLE");
+                assert_eq!(
+                    xquery_mediator.variables[1].expression.as_deref(),
+                    Some("$ctx:RATE")
+                );
+            }
+            _ => {
+                panic!("not an xslt and an xquery mediator");
+            }
+        }
+
+        //serialized mediators parse to the same mediators
+        for mediator in &mediators {
+            let output = mediator.to_string();
+            match parse_fragment(&output, FragmentContext::Mediator).unwrap() {
+                Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), output),
+                _ => {
+                    panic!("not a mediator");
+                }
+            }
+        }
+
+        //attribute values are escaped when serialized
+        for input in [
+            r#"<xslt key="conf:/xslt?a=1&amp;b=2"><feature name="urn:a&amp;b" value="true"/></xslt>"#,
+            r#"<xquery key="conf:/xq?a=1&amp;b=2"><variable name="a&amp;b" type="STRING" key="k&quot;1"/></xquery>"#,
+        ] {
+            match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+                Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), input),
+                _ => {
+                    panic!("not a mediator");
+                }
+            }
+        }
+
+        assert!(parse_fragment(r#"<xslt source="//order"/>"#, FragmentContext::Mediator).is_err());
+        let input = r#"<xquery key="q"><variable name="payload"/></xquery>"#;
+        assert!(parse_fragment(input, FragmentContext::Mediator).is_err());
+    }
+
+    #[test]
+    fn test_validate_mediator() {
+        let input = r#"
+        <inSequence>
+            <validate source="json-eval($)">
+                <schema key="conf:/schema/order.json"/>
+                <schema key="conf:/schema/customer.json"/>
+                <property name="http://javax.xml.XMLConstants/feature/secure-processing" value="true"/>
+                <on-fail>
+                    <log level="custom">
+                        <property name="error" expression="$ctx:ERROR_MESSAGE"/>
+                    </log>
+                    <drop/>
+                </on-fail>
+            </validate>
+        </inSequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        let paths: Vec<String> = program
+            .walk()
+            .iter()
+            .map(|(path, _)| path.to_string())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "inSequence",
+                "inSequence/mediator[0]",
+                "inSequence/mediator[0]/on-fail/mediator[0]",
+                "inSequence/mediator[0]/on-fail/mediator[1]"
+            ]
+        );
+
+        match program.mediators()[0] {
+            ast::Mediators::Validate(validate_mediator) => {
+                assert_eq!(validate_mediator.source.as_deref(), Some("json-eval($)"));
+                assert_eq!(validate_mediator.schemas.len(), 2);
+                assert_eq!(validate_mediator.properties[0].value, "true");
+            }
+            _ => {
+                panic!("not a validate mediator");
+            }
+        }
+
+        //serialized validate mediator parses to the same mediator
+        let output = program.mediators()[0].to_string();
+        match parse_fragment(&output, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), output),
+            _ => {
+                panic!("not a mediator");
+            }
+        }
+
+        //schema keys are escaped when serialized
+        let input = r#"<validate><schema key="conf:/schema?a=1&amp;b=2"/><on-fail><drop/></on-fail></validate>"#;
+        match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), input),
+            _ => {
+                panic!("not a mediator");
+            }
+        }
+
+        let input = r#"<validate><on-fail><drop/></on-fail></validate>"#;
+        assert!(parse_fragment(input, FragmentContext::Mediator).is_err());
+    }
+
+    #[test]
+    fn test_script_mediator() {
+        let input = r#"
+            <script language="js" key="conf:/scripts/transform.js" function="transform"/>
+            <script language="js"><![CDATA[
+    var total = mc.getProperty("TOTAL");
+    if (total < 100 && total > 0) { mc.setProperty("SMALL", "true"); }
+]]></script>
+        "#;
+
+        let mediators = match parse_fragment(input, FragmentContext::Mediators).unwrap() {
+            Fragment::Mediators(mediators) => mediators,
+            _ => {
+                panic!("not a mediator list");
+            }
+        };
+
+        let body = "\n    var total = mc.getProperty(\"TOTAL\");\n    if (total < 100 && total > 0) { mc.setProperty(\"SMALL\", \"true\"); }\n";
+        match &mediators[..] {
+            [ast::Mediators::Script(by_key), ast::Mediators::Script(inline)] => {
+                assert_eq!(by_key.key.as_deref(), Some("conf:/scripts/transform.js"));
+                assert_eq!(by_key.function.as_deref(), Some("transform"));
+                assert!(by_key.body.is_none());
+                assert_eq!(inline.language, "js");
+                assert_eq!(inline.body.as_deref(), Some(body));
+            }
+            _ => {
+                panic!("not two script mediators");
+            }
+        }
+
+        //serialized script keeps the inline code as is
+        let output = mediators[1].to_string();
+        match parse_fragment(&output, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(ast::Mediators::Script(script_mediator)) => {
+                assert_eq!(script_mediator.body.as_deref(), Some(body));
+            }
+            _ => {
+                panic!("not a script mediator");
+            }
+        }
+
+        //a body without cdata section keeps its indentation and newlines
+        let input = "<script language=\"js\">\n    var small = total &lt; 100;\n        mc.setProperty(\"SMALL\", small);\n</script>";
+        match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(ast::Mediators::Script(script_mediator)) => assert_eq!(
+                script_mediator.body.as_deref(),
+                Some("\n    var small = total < 100;\n        mc.setProperty(\"SMALL\", small);\n")
+            ),
+            _ => {
+                panic!("not a script mediator");
+            }
+        }
+
+        //attribute values are escaped when serialized
+        let input =
+            r#"<script language="js" key="conf:/scripts?a=1&amp;b=2" function="&quot;run&quot;"/>"#;
+        match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), input),
+            _ => {
+                panic!("not a mediator");
+            }
+        }
+
+        assert!(parse_fragment(r#"<script language="js"/>"#, FragmentContext::Mediator).is_err());
+    }
+
+    #[test]
+    fn test_iterate_and_aggregate_mediators() {
+        let input = r#"
+        <inSequence>
+            <iterate id="orders" expression="json-eval($.orders)" attachPath="json-eval($)" preservePayload="true" sequential="true">
+                <target>
+                    <sequence>
+                        <call>
+                            <endpoint key="orderService"/>
+                        </call>
+                    </sequence>
+                </target>
+            </iterate>
+            <aggregate id="orders">
+                <correlateOn expression="$ctx:BATCH_ID"/>
+                <completeCondition timeout="10">
+                    <messageCount min="-1" max="{get-property('COUNT')}"/>
+                </completeCondition>
+                <onComplete expression="json-eval($)" enclosingElementProperty="RESULTS">
+                    <respond/>
+                </onComplete>
+            </aggregate>
+        </inSequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        let paths: Vec<String> = program
+            .walk()
+            .iter()
+            .map(|(path, _)| path.to_string())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "inSequence",
+                "inSequence/mediator[0]",
+                "inSequence/mediator[0]/target/mediator[0]",
+                "inSequence/mediator[1]",
+                "inSequence/mediator[1]/onComplete/mediator[0]"
+            ]
+        );
+
+        match program.mediators()[..] {
+            [ast::Mediators::Iterate(iterate_mediator), _, ast::Mediators::Aggregate(aggregate_mediator), _] =>
+            {
+                assert_eq!(iterate_mediator.id.as_deref(), Some("orders"));
+                assert_eq!(iterate_mediator.sequential, Some(true));
+                assert!(iterate_mediator.target.endpoint.is_none());
+                assert_eq!(
+                    aggregate_mediator.correlate_on.as_deref(),
+                    Some("$ctx:BATCH_ID")
+                );
+                let complete_condition = aggregate_mediator.complete_condition.as_ref().unwrap();
+                assert_eq!(complete_condition.timeout, Some(10));
+                assert_eq!(complete_condition.min_messages.as_deref(), Some("-1"));
+                assert_eq!(
+                    aggregate_mediator
+                        .on_complete
+                        .enclosing_element_property
+                        .as_deref(),
+                    Some("RESULTS")
+                );
+            }
+            _ => {
+                panic!("not an iterate and an aggregate mediator");
+            }
+        }
+
+        //serialized sequence parses to the same sequence
+        let output = program.to_string();
+        let reparsed = Parser::new(output.as_bytes()).parse_progarm().unwrap();
+        assert_eq!(reparsed.to_string(), output);
+
+        //attribute values are escaped when serialized
+        for input in [
+            r#"<iterate id="a&amp;b" expression="//order"><target sequence="s&amp;1"></target></iterate>"#,
+            r#"<aggregate id="a&amp;b"><onComplete expression="//order" sequence="s&amp;1" enclosingElementProperty="p&quot;1"></onComplete></aggregate>"#,
+        ] {
+            match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+                Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), input),
+                _ => {
+                    panic!("not a mediator");
+                }
+            }
+        }
+
+        let input = r#"<iterate expression="//order"/>"#;
+        assert!(parse_fragment(input, FragmentContext::Mediator).is_err());
+        let input = r#"<aggregate><correlateOn expression="//id"/></aggregate>"#;
+        assert!(parse_fragment(input, FragmentContext::Mediator).is_err());
+    }
+
+    #[test]
+    fn test_clone_mediator() {
+        let input = r#"
+        <clone id="notify" continueParent="true">
+            <target sequence="audit"/>
+            <target endpoint="crm"/>
+            <target to="http://archive:8080/store">
+                <sequence>
+                    <log level="full"/>
+                </sequence>
+                <endpoint>
+                    <address uri="http://archive:8080/store"/>
+                </endpoint>
+            </target>
+        </clone>
+        "#;
+
+        let clone_mediator = match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(mediator @ ast::Mediators::Clone(_)) => mediator,
+            _ => {
+                panic!("not a clone mediator");
+            }
+        };
+
+        let branches: Vec<String> = clone_mediator
+            .branches()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(branches, vec!["target[2]"]);
+
+        match &clone_mediator {
+            ast::Mediators::Clone(clone) => {
+                assert_eq!(clone.continue_parent, Some(true));
+                assert_eq!(clone.targets.len(), 3);
+                assert!(matches!(
+                    &clone.targets[0].sequence,
+                    Some(ast::SequenceRef::Key(key)) if key == "audit"
+                ));
+                assert!(matches!(
+                    &clone.targets[1].endpoint,
+                    Some(ast::EndpointRef::Key(key)) if key == "crm"
+                ));
+                assert!(matches!(
+                    &clone.targets[2].endpoint,
+                    Some(ast::EndpointRef::Inline(_))
+                ));
+            }
+            _ => {
+                panic!("not a clone mediator");
+            }
+        }
+
+        //serialized clone parses to the same clone
+        let output = clone_mediator.to_string();
+        match parse_fragment(&output, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), output),
+            _ => {
+                panic!("not a mediator");
+            }
+        }
+
+        //attribute values are escaped when serialized
+        let input = r#"<clone id="a&amp;b"><target to="http://a?x=1&amp;y=2" soapAction="urn:a&amp;b" sequence="s&amp;1"></target><target endpoint="ep&quot;1"></target></clone>"#;
+        match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), input),
+            _ => {
+                panic!("not a mediator");
+            }
+        }
+
+        let input =
+            r#"<clone><target sequence="audit"><sequence><drop/></sequence></target></clone>"#;
+        assert!(parse_fragment(input, FragmentContext::Mediator).is_err());
+    }
+
+    #[test]
+    fn test_flow_control_mediators() {
+        let input = r#"
+            <respond/>
+            <drop></drop>
+            <loopback>
+            </loopback>
+        "#;
+
+        match parse_fragment(input, FragmentContext::Mediators).unwrap() {
+            Fragment::Mediators(mediators) => {
+                assert!(matches!(
+                    mediators[..],
+                    [
+                        ast::Mediators::Respond,
+                        ast::Mediators::Drop,
+                        ast::Mediators::Loopback
+                    ]
+                ));
+                let output: Vec<String> = mediators.iter().map(|m| m.to_string()).collect();
+                assert_eq!(output.concat(), "<respond/><drop/><loopback/>");
+            }
+            _ => {
+                panic!("not a mediator list");
+            }
+        }
+
+        assert!(parse_fragment("<drop><log/></drop>", FragmentContext::Mediator).is_err());
+    }
+
+    #[test]
+    fn test_send_mediator() {
+        let input = r#"
+            <send/>
+            <send receive="handleResponse" buildmessage="true">
+                <endpoint key="stockQuote"/>
+            </send>
+            <send>
+                <endpoint>
+                    <address uri="http://localhost:9000/services/SimpleStockQuoteService"/>
+                </endpoint>
+            </send>
+        "#;
+
+        let mediators = match parse_fragment(input, FragmentContext::Mediators).unwrap() {
+            Fragment::Mediators(mediators) => mediators,
+            _ => {
+                panic!("not a mediator list");
+            }
+        };
+
+        match &mediators[..] {
+            [ast::Mediators::Send(bare), ast::Mediators::Send(by_key), ast::Mediators::Send(inline)] =>
+            {
+                assert!(bare.endpoint.is_none());
+                assert_eq!(by_key.receive.as_deref(), Some("handleResponse"));
+                assert_eq!(by_key.build_message, Some(true));
+                assert!(matches!(
+                    &by_key.endpoint,
+                    Some(ast::EndpointRef::Key(key)) if key == "stockQuote"
+                ));
+                assert!(matches!(
+                    &inline.endpoint,
+                    Some(ast::EndpointRef::Inline(_))
+                ));
+            }
+            _ => {
+                panic!("not three send mediators");
+            }
+        }
+
+        let output: Vec<String> = mediators.iter().map(|m| m.to_string()).collect();
+        assert_eq!(output[0], "<send/>");
+        assert_eq!(
+            output[1],
+            r#"<send receive="handleResponse" buildmessage="true"><endpoint key="stockQuote"/></send>"#
+        );
+
+        //the receive sequence is escaped when serialized
+        let input = r#"<send receive="a&amp;b"/>"#;
+        match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), input),
+            _ => {
+                panic!("not a mediator");
+            }
+        }
+
+        let input = r#"<send><endpoint key="a"/><endpoint key="b"/></send>"#;
+        assert!(parse_fragment(input, FragmentContext::Mediator).is_err());
+    }
+
+    #[test]
+    fn test_callout_mediator() {
+        let input = r#"
+        <callout serviceURL="http://localhost:9000/services/SimpleStockQuoteService" action="urn:getQuote">
+            <source xmlns:s11="http://schemas.xmlsoap.org/soap/envelope/" xpath="s11:Envelope/s11:Body/child::*[fn:position()=1]"/>
+            <target key="QUOTE"/>
+            <enableSec policy="conf:/repository/policy.xml"/>
+        </callout>
+        "#;
+
+        let callout_mediator = match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(ast::Mediators::Callout(callout_mediator)) => callout_mediator,
+            _ => {
+                panic!("not a callout mediator");
+            }
+        };
+
+        assert_eq!(callout_mediator.action.as_deref(), Some("urn:getQuote"));
+        assert!(callout_mediator.source.as_ref().unwrap().xpath.is_some());
+        assert_eq!(
+            callout_mediator.target.as_ref().unwrap().key.as_deref(),
+            Some("QUOTE")
+        );
+        assert_eq!(
+            callout_mediator
+                .enable_sec
+                .as_ref()
+                .unwrap()
+                .policy
+                .as_deref(),
+            Some("conf:/repository/policy.xml")
+        );
+
+        assert_eq!(
+            callout_mediator.to_string(),
+            r#"<callout serviceURL="http://localhost:9000/services/SimpleStockQuoteService" action="urn:getQuote"><source xmlns:s11="http://schemas.xmlsoap.org/soap/envelope/" xpath="s11:Envelope/s11:Body/child::*[fn:position()=1]"/><target key="QUOTE"/><enableSec policy="conf:/repository/policy.xml"/></callout>"#
+        );
+
+        //declarations of enclosing elements are written on the element using them
+        let input = r#"<callout xmlns:q="urn:quote" serviceURL="http://quotes"><target xpath="//q:price"/></callout>"#;
+        match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(mediator) => assert_eq!(
+                mediator.to_string(),
+                r#"<callout serviceURL="http://quotes"><target xmlns:q="urn:quote" xpath="//q:price"/></callout>"#
+            ),
+            _ => {
+                panic!("not a mediator");
+            }
+        }
+
+        //keys and policies are escaped when serialized
+        let input = r#"<callout endpointKey="ep&amp;1"><configuration axis2xml="a&amp;b.xml" repository="repo&quot;"/><source key="in&amp;put" type="envelope"/><target key="out&amp;put"/><enableSec policy="conf:/p?a=1&amp;b=2" outboundPolicy="o&amp;p" inboundPolicy="i&amp;p"/></callout>"#;
+        match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), input),
+            _ => {
+                panic!("not a mediator");
+            }
+        }
+
+        let input = r#"<callout action="urn:getQuote"><target key="QUOTE"/></callout>"#;
+        assert!(parse_fragment(input, FragmentContext::Mediator).is_err());
+    }
+
+    #[test]
+    fn test_makefault_mediator() {
+        let input = r#"
+        <faultSequence>
+            <makefault version="soap11" response="true">
+                <code xmlns:soap11Env="http://schemas.xmlsoap.org/soap/envelope/" value="soap11Env:Server"/>
+                <reason expression="get-property('ERROR_MESSAGE')"/>
+                <role>urn:gateway</role>
+                <detail>order &lt;42&gt; rejected</detail>
+            </makefault>
+            <makefault version="pox">
+                <reason value="invalid order"/>
+                <detail expression="$ctx:ERROR_DETAIL"/>
+            </makefault>
+        </faultSequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        let mediators = program.mediators();
+
+        match mediators.as_slice() {
+            [ast::Mediators::Makefault(soap), ast::Mediators::Makefault(pox)] => {
+                assert_eq!(soap.version, Some(ast::FaultVersion::Soap11));
+                assert!(matches!(
+                    &soap.code,
+                    Some(ast::FaultValue::Value(code)) if code == "soap11Env:Server"
+                ));
+                assert_eq!(soap.role.as_deref(), Some("urn:gateway"));
+                assert!(pox.code.is_none());
+                assert_eq!(
+                    mediators[0].expressions(),
+                    vec!["get-property('ERROR_MESSAGE')"]
+                );
+                assert_eq!(mediators[1].expressions(), vec!["$ctx:ERROR_DETAIL"]);
+            }
+            _ => {
+                panic!("not two makefault mediators");
+            }
+        }
+
+        assert_eq!(
+            mediators[0].to_string(),
+            r#"<makefault xmlns:soap11Env="http://schemas.xmlsoap.org/soap/envelope/" version="soap11" response="true"><code value="soap11Env:Server"/><reason expression="get-property(&apos;ERROR_MESSAGE&apos;)"/><role>urn:gateway</role><detail>order &lt;42&gt; rejected</detail></makefault>"#
+        );
+
+        //the fault code still resolves when the output is parsed again
+        let output = mediators[0].to_string();
+        match parse_fragment(&output, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), output),
+            _ => {
+                panic!("not a mediator");
+            }
+        }
+
+        let input = r#"<makefault version="soap12"><reason value="no code"/></makefault>"#;
+        assert!(parse_fragment(input, FragmentContext::Mediator).is_err());
+    }
+
+    #[test]
+    fn test_message_store_and_processor() {
+        let input = r#"
+        <messageStore xmlns="http://ws.apache.org/ns/synapse" name="OrderStore" class="org.apache.synapse.message.store.impl.jms.JmsStore">
+            <parameter name="store.jms.destination">orders</parameter>
+            <parameter name="store.jms.JMSSpecVersion">1.1</parameter>
+        </messageStore>
+        <messageProcessor xmlns="http://ws.apache.org/ns/synapse" name="OrderForwarder" class="org.apache.synapse.message.processor.impl.forwarder.ScheduledMessageForwardingProcessor" messageStore="OrderStore" targetEndpoint="OrderService">
+            <parameter name="interval">1000</parameter>
+        </messageProcessor>
+        <sequence xmlns="http://ws.apache.org/ns/synapse" name="StoreOrder">
+            <store messageStore="OrderStore" sequence="BeforeStore"/>
+        </sequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+
+        match program.ast_nodes.as_slice() {
+            [ast::AstNode::MessageStore(message_store), ast::AstNode::MessageProcessor(message_processor), ast::AstNode::Sequence(_)] =>
+            {
+                assert_eq!(message_store.name, "OrderStore");
+                assert_eq!(message_store.parameters.len(), 2);
+                assert_eq!(message_store.parameters[0].value, "orders");
+                assert_eq!(message_processor.message_store, "OrderStore");
+                assert_eq!(
+                    message_processor.target_endpoint.as_deref(),
+                    Some("OrderService")
+                );
+            }
+            _ => {
+                panic!("not a message store, processor and sequence");
+            }
+        }
+
+        assert_eq!(
+            program
+                .walk()
+                .into_iter()
+                .map(|(path, _)| path.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "messageStore[OrderStore]",
+                "messageProcessor[OrderForwarder]",
+                "sequence[StoreOrder]",
+                "sequence[StoreOrder]/mediator[0]"
+            ]
+        );
+
+        assert_eq!(
+            program.mediators()[0].to_string(),
+            r#"<store messageStore="OrderStore" sequence="BeforeStore"/>"#
+        );
+        assert_eq!(
+            program.ast_nodes[1].to_string(),
+            r#"<messageProcessor name="OrderForwarder" xmlns="http://ws.apache.org/ns/synapse" class="org.apache.synapse.message.processor.impl.forwarder.ScheduledMessageForwardingProcessor" messageStore="OrderStore" targetEndpoint="OrderService"><parameter name="interval">1000</parameter></messageProcessor>"#
+        );
+
+        //connection urls and other attribute values are escaped when serialized
+        let input = r#"<messageStore name="a&amp;b" class="org.apache.synapse.message.store.impl.jdbc.JDBCMessageStore"><parameter name="store.jdbc.connection.url">jdbc:mysql://db/store?useSSL=false&amp;serverTimezone=UTC</parameter></messageStore><messageProcessor name="p&quot;1" class="forwarder" messageStore="a&amp;b" targetEndpoint="ep&amp;1"></messageProcessor><inSequence><store messageStore="a&amp;b" sequence="s&amp;1"/></inSequence>"#;
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        assert_eq!(program.to_string(), input);
+
+        let input = r#"<messageProcessor name="OrderForwarder" messageStore="OrderStore"/>"#;
+        assert!(Parser::new(input.as_bytes()).parse_progarm().is_err());
+    }
+
+    #[test]
+    fn test_cache_mediator() {
+        let input = r#"
+        <inSequence>
+            <cache collector="false" timeout="60" maxMessageSize="2000">
+                <onCacheHit>
+                    <log level="custom">
+                        <property name="cache" value="hit"/>
+                    </log>
+                    <respond/>
+                </onCacheHit>
+                <protocol type="HTTP">
+                    <methods>GET,HEAD</methods>
+                    <headersToExcludeInHash/>
+                    <responseCodes>2[0-9][0-9]</responseCodes>
+                    <enableCacheControl>false</enableCacheControl>
+                </protocol>
+                <implementation maxSize="1000"/>
+            </cache>
+            <call>
+                <endpoint key="orders"/>
+            </call>
+            <cache collector="true"/>
+        </inSequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+
+        let cache_mediator = match program.mediators()[0] {
+            ast::Mediators::Cache(cache_mediator) => cache_mediator,
+            _ => {
+                panic!("not a cache mediator");
+            }
+        };
+        assert_eq!(cache_mediator.collector, Some(false));
+        assert_eq!(cache_mediator.timeout, Some(60));
+        let protocol = cache_mediator.protocol.as_ref().unwrap();
+        assert_eq!(protocol.methods.as_deref(), Some("GET,HEAD"));
+        assert_eq!(protocol.headers_to_exclude_in_hash.as_deref(), Some(""));
+        assert_eq!(protocol.enable_cache_control, Some(false));
+        assert_eq!(
+            cache_mediator.implementation.as_ref().unwrap().max_size,
+            Some(1000)
+        );
+
+        //mediators run on a cache hit are part of the walk
+        let paths: Vec<String> = program
+            .walk()
+            .into_iter()
+            .map(|(path, _)| path.to_string())
+            .collect();
+        assert!(paths.contains(&"inSequence/mediator[0]/onCacheHit/mediator[1]".to_string()));
+        assert_eq!(program.mediators().len(), 5);
+
+        assert_eq!(
+            program.mediators()[0].to_string(),
+            r#"<cache collector="false" timeout="60" maxMessageSize="2000"><onCacheHit><log level="custom"><property name="cache" value="hit"/></log><respond/></onCacheHit><protocol type="HTTP"><methods>GET,HEAD</methods><headersToExcludeInHash></headersToExcludeInHash><responseCodes>2[0-9][0-9]</responseCodes><enableCacheControl>false</enableCacheControl></protocol><implementation maxSize="1000"/></cache>"#
+        );
+
+        //attribute values are escaped when serialized
+        let input = r#"<cache id="a&amp;b"><onCacheHit sequence="hit&amp;1"></onCacheHit><protocol type="HTTP&amp;S"></protocol><implementation type="memory&quot;"/></cache>"#;
+        match parse_fragment(input, FragmentContext::Mediator).unwrap() {
+            Fragment::Mediator(mediator) => assert_eq!(mediator.to_string(), input),
+            _ => {
+                panic!("not a mediator");
+            }
+        }
+    }
+
+    #[test]
+    fn test_http_endpoint() {
+        let input = r#"
+            <endpoint name="httpbin">
+                <http method="GET" uri-template="http://httpbin:80/get">
+                    <timeout>
+                        <duration>15000</duration>
+                        <responseAction>fault</responseAction>
+                    </timeout>
+                    <suspendOnFailure>
+                        <errorCodes>-1</errorCodes>
+                        <initialDuration>0</initialDuration>
+                        <progressionFactor>1.0</progressionFactor>
+                        <maximumDuration>0</maximumDuration>
+                    </suspendOnFailure>
+                    <markForSuspension>
+                        <errorCodes>101504, 101505</errorCodes>
+                        <retriesBeforeSuspension>3</retriesBeforeSuspension>
+                    </markForSuspension>
+                </http>
+            </endpoint>
+        "#;
+
+        let endpoint = match parse_fragment(input, FragmentContext::Endpoint).unwrap() {
+            Fragment::Endpoint(ast::EndpointRef::Inline(endpoint)) => endpoint,
+            _ => {
+                panic!("not an inline endpoint");
+            }
+        };
+
+        assert_eq!(endpoint.name.as_deref(), Some("httpbin"));
+        match &endpoint.kind {
+            endpoint::EndpointKind::Http(http_endpoint) => {
+                assert_eq!(http_endpoint.method, Some(ast::HttpMethod::Get));
+                assert_eq!(http_endpoint.uri_template, "http://httpbin:80/get");
+
+                let timeout = http_endpoint.qos.timeout.as_ref().unwrap();
+                assert_eq!(timeout.duration, Some(15000));
+                assert_eq!(
+                    timeout.response_action,
+                    Some(endpoint::ResponseAction::Fault)
+                );
+
+                let suspend_on_failure = http_endpoint.qos.suspend_on_failure.as_ref().unwrap();
+                assert_eq!(suspend_on_failure.error_codes, vec![-1]);
+                assert_eq!(suspend_on_failure.initial_duration, Some(0));
+                assert_eq!(suspend_on_failure.progression_factor, Some(1.0));
+                assert_eq!(suspend_on_failure.maximum_duration, Some(0));
+
+                let mark_for_suspension = http_endpoint.qos.mark_for_suspension.as_ref().unwrap();
+                assert_eq!(mark_for_suspension.error_codes, vec![101504, 101505]);
+                assert_eq!(mark_for_suspension.retries_before_suspension, Some(3));
+                assert_eq!(mark_for_suspension.retry_delay, None);
+            }
+            _ => {
+                panic!("not an http endpoint");
+            }
+        }
+
+        //serialized endpoint parses to the same endpoint
+        let output = endpoint.to_string();
+        match parse_fragment(&output, FragmentContext::Endpoint).unwrap() {
+            Fragment::Endpoint(endpoint) => assert_eq!(endpoint.to_string(), output),
+            _ => {
+                panic!("not an endpoint");
+            }
+        }
 
-        //parse all elements
-        while self.current_event.as_ref() != Some(&XmlEvent::EndDocument) {
-            let node = match self.current_event.as_ref() {
-                Some(XmlEvent::StartElement { name, .. }) if name.local_name == "inSequence" => {
-                    self.parse_in_sequence()
-                }
+        //query strings are escaped when serialized
+        for input in [
+            r#"<endpoint name="orders&amp;items"><http method="GET" uri-template="http://h/x?a=1&amp;b={uri.var.b}"></http></endpoint>"#,
+            r#"<endpoint><address uri="http://h/x?a=1&amp;b=2"></address></endpoint>"#,
+            r#"<endpoint key="conf:/endpoints?a=1&amp;b=2"/>"#,
+        ] {
+            match parse_fragment(input, FragmentContext::Endpoint).unwrap() {
+                Fragment::Endpoint(endpoint) => assert_eq!(endpoint.to_string(), input),
                 _ => {
-                    bail!("error");
+                    panic!("not an endpoint");
                 }
-            };
-            println!("{:?}", node);
-            ast_nodes.push(node?);
+            }
         }
-        Result::Ok(ast::Program { ast_nodes })
+
+        let input = r#"<endpoint><http uri-template="/"><timeout><duration>-1</duration></timeout></http></endpoint>"#;
+        assert!(parse_fragment(input, FragmentContext::Endpoint).is_err());
+        let input = r#"<endpoint><http uri-template="/"><suspendOnFailure><progressionFactor>0.5</progressionFactor></suspendOnFailure></http></endpoint>"#;
+        assert!(parse_fragment(input, FragmentContext::Endpoint).is_err());
     }
 
-    //--------------------------------------------------------------------------------//
+    #[test]
+    fn test_group_endpoints() {
+        let input = r#"
+            <endpoint name="group">
+                <failover buildMessage="true">
+                    <endpoint key="primary"/>
+                    <endpoint>
+                        <loadbalance algorithm="org.apache.synapse.endpoints.algorithms.RoundRobin">
+                            <endpoint>
+                                <address uri="http://one:8080/service" format="soap11">
+                                    <timeout><duration>30000</duration></timeout>
+                                </address>
+                            </endpoint>
+                            <endpoint><wsdl uri="file:service.wsdl" service="Service" port="Port"/></endpoint>
+                        </loadbalance>
+                    </endpoint>
+                    <endpoint><recipientlist><endpoint><default/></endpoint></recipientlist></endpoint>
+                </failover>
+            </endpoint>
+        "#;
 
-    fn parse_in_sequence(&mut self) -> Result<ast::AstNode> {
-        let mut in_sequence = ast::InSequence {
-            mediators: Vec::new(),
+        let endpoint = match parse_fragment(input, FragmentContext::Endpoint).unwrap() {
+            Fragment::Endpoint(ast::EndpointRef::Inline(endpoint)) => endpoint,
+            _ => {
+                panic!("not an inline endpoint");
+            }
         };
 
-        //current event is start element of inSequence walk to the next event (start element of mediator)
-        self.current_event = self.event_reader.next().ok();
-        while self.current_event
-            != Some(XmlEvent::EndElement {
-                name: OwnedName::local("inSequence"),
-            })
-        {
-            let mediator = self.parse_mediator().context("error parsing mediator")?;
-            match mediator {
-                ast::AstNode::Mediator(mediator) => {
-                    in_sequence.mediators.push(mediator);
-                }
+        let failover = match &endpoint.kind {
+            endpoint::EndpointKind::Failover(failover) => failover,
+            _ => {
+                panic!("not a failover endpoint");
+            }
+        };
+        assert_eq!(failover.build_message, Some(true));
+        assert_eq!(failover.members.len(), 3);
+        assert!(matches!(&failover.members[0], ast::EndpointRef::Key(key) if key == "primary"));
+
+        let loadbalance = match &failover.members[1] {
+            ast::EndpointRef::Inline(member) => match &member.kind {
+                endpoint::EndpointKind::Loadbalance(loadbalance) => loadbalance,
                 _ => {
-                    bail!("error parsing mediator");
+                    panic!("not a loadbalance endpoint");
                 }
+            },
+            _ => {
+                panic!("not an inline endpoint");
+            }
+        };
+        assert_eq!(
+            loadbalance.algorithm.as_deref(),
+            Some("org.apache.synapse.endpoints.algorithms.RoundRobin")
+        );
+        assert_eq!(loadbalance.members.len(), 2);
+
+        //serialized endpoint parses to the same endpoint
+        let output = endpoint.to_string();
+        match parse_fragment(&output, FragmentContext::Endpoint).unwrap() {
+            Fragment::Endpoint(endpoint) => assert_eq!(endpoint.to_string(), output),
+            _ => {
+                panic!("not an endpoint");
             }
         }
 
-        self.current_event = self.event_reader.next().ok();
+        let input = r#"<endpoint><failover><address uri="http://one"/></failover></endpoint>"#;
+        assert!(parse_fragment(input, FragmentContext::Endpoint).is_err());
 
-        //return in_sequence as ast Sequence node
-        Result::Ok(ast::AstNode::Sequence(ast::Sequences::InSequence(
-            in_sequence,
-        )))
-    }
+        let input =
+            r#"<endpoint><address uri="http://one"/><http uri-template="http://two"/></endpoint>"#;
+        assert!(parse_fragment(input, FragmentContext::Endpoint).is_err());
 
-    //--------------------------------------------------------------------------------//
+        let input = r#"<endpoint><wsdl uri="http://h/service?wsdl&amp;v=2" service="Service" port="Port"/></endpoint>"#;
+        match parse_fragment(input, FragmentContext::Endpoint).unwrap() {
+            Fragment::Endpoint(endpoint) => assert_eq!(
+                endpoint.to_string(),
+                r#"<endpoint><wsdl uri="http://h/service?wsdl&amp;v=2" service="Service" port="Port"></wsdl></endpoint>"#
+            ),
+            _ => {
+                panic!("not an endpoint");
+            }
+        }
 
-    fn parse_mediator(&mut self) -> Result<ast::AstNode> {
-        match self.current_event.as_ref() {
-            Some(XmlEvent::StartElement { name, .. }) => match name.local_name.as_str() {
-                "log" => self.parse_log_mediator(),
-                "property" => self.parse_property(),
-                _ => {
-                    bail!("not a supported mediator: element {}", name.local_name);
-                }
-            },
-            Some(XmlEvent::EndElement { name, .. }) => match name.local_name.as_str() {
-                "log" => self.parse_log_mediator(),
-                "property" => self.parse_property(),
-                _ => {
-                    bail!("not a supported mediator: element {}", name.local_name);
+        //format and optimize are typed, unknown values are rejected
+        let input = r#"<endpoint><address uri="http://one" format="soap12" optimize="mtom"></address></endpoint>"#;
+        match parse_fragment(input, FragmentContext::Endpoint).unwrap() {
+            Fragment::Endpoint(ast::EndpointRef::Inline(endpoint)) => {
+                match &endpoint.kind {
+                    endpoint::EndpointKind::Address(address) => {
+                        assert_eq!(address.format, Some(endpoint::EndpointFormat::Soap12));
+                        assert_eq!(address.optimize, Some(endpoint::Optimize::Mtom));
+                    }
+                    _ => {
+                        panic!("not an address endpoint");
+                    }
                 }
-            },
+                assert_eq!(endpoint.to_string(), input);
+            }
             _ => {
-                bail!("not a supported mediator");
+                panic!("not an inline endpoint");
             }
         }
-    }
+        let input = r#"<endpoint><default format="soap13"/></endpoint>"#;
+        assert!(parse_fragment(input, FragmentContext::Endpoint).is_err());
+        let input = r#"<endpoint><default optimize="MTOM"/></endpoint>"#;
+        assert!(parse_fragment(input, FragmentContext::Endpoint).is_err());
 
-    fn parse_log_mediator(&mut self) -> Result<ast::AstNode> {
-        let mut log_level = String::new();
+        //endpoint artifacts keep their namespace
+        let input = r#"<endpoint name="one" xmlns="http://ws.apache.org/ns/synapse"><address uri="http://one"></address></endpoint>"#;
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        assert_eq!(program.to_string(), input);
 
-        //get log level
-        match self.current_event.as_ref() {
-            Some(XmlEvent::StartElement { attributes, .. }) => {
-                for attr in attributes {
-                    if attr.name.local_name == "level" {
-                        log_level = attr.value.clone();
-                    }
-                }
+        //properties and the description written by integration studio are kept
+        let input = r#"
+        <endpoint name="orders" xmlns="http://ws.apache.org/ns/synapse">
+            <address uri="http://orders"/>
+            <property name="TENANT" value="acme" scope="axis2"/>
+            <description/>
+        </endpoint>
+        "#;
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        match &program.ast_nodes[0] {
+            ast::AstNode::Endpoint(endpoint) => {
+                assert!(matches!(endpoint.kind, endpoint::EndpointKind::Address(_)));
+                assert_eq!(endpoint.properties[0].name, "TENANT");
+                assert_eq!(endpoint.properties[0].scope, Some(ast::Scope::Axis2));
+                assert_eq!(endpoint.description.as_deref(), Some(""));
             }
             _ => {
-                bail!("not log level specified");
+                panic!("not an endpoint");
             }
         }
+        assert_eq!(
+            program.to_string(),
+            r#"<endpoint name="orders" xmlns="http://ws.apache.org/ns/synapse"><address uri="http://orders"></address><property name="TENANT" value="acme" scope="axis2"/><description></description></endpoint>"#
+        );
+    }
 
-        //create log mediator node
-        let mut log_mediator = ast::LogMediator {
-            level: log_level,
-            properties: vec![],
-        };
+    #[test]
+    fn test_root_fragments() {
+        let input = r#"
+        <log level="custom">
+            <property name="/validate" value="fragment" />
+        </log>
+        "#;
 
-        //current event is start element of log mediator walk to the next event (start element of property mediator)
-        self.current_event = self.event_reader.next().ok();
+        let mut parser = Parser::new(input.as_bytes());
+        assert!(parser.parse_progarm().is_err());
 
-        //parse log content properties
-        while self.current_event
-            != Some(XmlEvent::EndElement {
-                name: OwnedName::local("log"),
-            })
-        {
-            match self.parse_mediator() {
-                Result::Ok(ast::AstNode::Mediator(ast::Mediators::Property(property))) => {
-                    log_mediator.properties.push(property);
-                }
-                _ => {
-                    bail!("error parsing log mediator");
-                }
+        let mut parser = Parser::new(input.as_bytes()).allow_fragments(true);
+        let program = parser.parse_progarm().unwrap();
+
+        assert_eq!(program.ast_nodes.len(), 1);
+        match &program.ast_nodes[0] {
+            ast::AstNode::Mediator(ast::Mediators::Log(log_mediator)) => {
+                assert_eq!(log_mediator.level, "custom");
+                assert_eq!(log_mediator.properties[0].value, "fragment");
+            }
+            _ => {
+                panic!("not a log mediator");
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_fragment() {
+        let input = r#"
+            <log level="full" />
+            <property name="foo" value="bar" />
+        "#;
+
+        match parse_fragment(input, FragmentContext::Mediators).unwrap() {
+            Fragment::Mediators(mediators) => {
+                assert_eq!(mediators.len(), 2);
+                assert!(matches!(mediators[1], ast::Mediators::Property(_)));
+            }
+            _ => {
+                panic!("not a mediator list");
             }
-            //skip the read property element
-            self.current_event = self.event_reader.next().ok();
         }
 
-        self.current_event = self.event_reader.next().ok();
+        assert!(parse_fragment(input, FragmentContext::Mediator).is_err());
+        assert!(matches!(
+            parse_fragment(r#"<log level="full" />"#, FragmentContext::Mediator),
+            Ok(Fragment::Mediator(ast::Mediators::Log(_)))
+        ));
+    }
 
-        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Log(log_mediator)))
+    #[test]
+    fn test_parse_partial() {
+        let input = r#"<api context="/orders" name="orders">
+    <resource methods="GET">
+        <inSequence><respond/></inSequence>
+    </resource>
+</api>
+<inSequence>
+    <unknown/>
+</inSequence>"#;
+
+        let partial = Parser::new(input.as_bytes()).parse_partial();
+        assert_eq!(partial.program.ast_nodes.len(), 1);
+        assert!(matches!(partial.program.ast_nodes[0], ast::AstNode::Api(_)));
+
+        let error = partial.error.unwrap();
+        assert_eq!(error.row, 6);
+        assert!(format!("{:#}", error.error).contains("element unknown"));
+
+        let input = r#"<inSequence><respond/></inSequence>"#;
+        let partial = Parser::new(input.as_bytes()).parse_partial();
+        assert!(partial.error.is_none());
+        assert_eq!(partial.program.mediators().len(), 1);
     }
 
-    fn parse_property(&mut self) -> Result<ast::AstNode> {
-        let mut property_name = String::new();
-        let mut property_value = String::new();
+    #[test]
+    fn test_parse_documents() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <inSequence>
+            <log level="full" />
+        </inSequence>
+        <?xml version="1.0" encoding="UTF-8"?>
+        <inSequence>
+            <log level="custom" />
+        </inSequence>
+        "#;
 
-        match self.current_event.as_ref() {
-            Some(XmlEvent::StartElement { attributes, .. }) => {
-                for attr in attributes {
-                    if attr.name.local_name == "name" {
-                        property_name = attr.value.clone();
-                    }
-                    if attr.name.local_name == "value" {
-                        property_value = attr.value.clone();
-                    }
-                }
+        let programs = parse_documents(input.as_bytes()).unwrap();
+        assert_eq!(programs.len(), 2);
+        assert_eq!(programs[1].mediators().len(), 1);
+
+        let input = r#"<inSequence><log level="full" /></inSequence>"#;
+        assert_eq!(parse_documents(input.as_bytes()).unwrap().len(), 1);
+
+        //declarations inside script code and comments do not start a document
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <inSequence>
+            <!-- <?xml version="1.0"?> -->
+            <script language="js"><![CDATA[var s = '<?xml version="1.0"?>';]]></script>
+        </inSequence>
+        <?xml version="1.0" encoding="ISO-8859-1"?>
+        <inSequence><log level="full" /></inSequence>
+        "#;
+        let programs = parse_documents(input.as_bytes()).unwrap();
+        assert_eq!(programs.len(), 2);
+        assert_eq!(programs[0].mediators().len(), 1);
+
+        //documents are decoded one by one
+        let input = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><inSequence><property name=\"a\" value=\"caf\xe9\" /></inSequence>
+        <?xml version=\"1.0\" encoding=\"UTF-8\"?><inSequence><log level=\"full\" /></inSequence>";
+        assert_eq!(parse_documents(input).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_non_utf8_input() {
+        let input = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?>
+        <inSequence>
+            <log level=\"custom\">
+                <property name=\"/validate\" value=\"caf\xe9\" />
+            </log>
+        </inSequence>";
+
+        let program = Parser::new(&input[..]).parse_progarm().unwrap();
+        match program.mediators()[0] {
+            ast::Mediators::Log(log_mediator) => {
+                assert_eq!(log_mediator.properties[0].value, "caf\u{e9}");
             }
             _ => {
-                bail!("error");
+                panic!("not a log mediator");
             }
         }
 
-        //skip end element of property
-        self.current_event = self.event_reader.next().ok();
+        let input = r#"<?xml version="1.0" encoding="UTF-16"?><inSequence><log level="full" /></inSequence>"#;
+        let mut utf16 = vec![0xFF, 0xFE];
+        for unit in input.encode_utf16() {
+            utf16.extend(unit.to_le_bytes());
+        }
 
-        Result::Ok(ast::AstNode::Mediator(ast::Mediators::Property(
-            ast::PropertyMediator {
-                name: property_name,
-                value: property_value,
-            },
-        )))
+        let program = Parser::new(&utf16[..]).parse_progarm().unwrap();
+        assert_eq!(program.mediators().len(), 1);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::{ast, Parser};
+    #[test]
+    fn test_bom_and_crlf_input() {
+        let mut input = vec![0xEF, 0xBB, 0xBF];
+        input.extend(b"<inSequence>\r\n    <log level=\"full\" />\r\n    <script language=\"js\"><![CDATA[var a = 1;\r\nvar b = 2;]]></script>\r\n</inSequence>\r\n");
+
+        let program = Parser::new(&input[..]).parse_progarm().unwrap();
+        assert_eq!(program.mediators().len(), 2);
+        assert_eq!(
+            program.format,
+            ast::SourceFormat {
+                bom: true,
+                crlf: true
+            }
+        );
+
+        //the serialized program keeps the byte order mark and the line endings of the source
+        let output = program.to_string();
+        assert!(output.starts_with('\u{feff}'));
+        assert!(output.contains("var a = 1;\r\nvar b = 2;"));
+        assert_eq!(
+            Parser::new(output.as_bytes())
+                .parse_progarm()
+                .unwrap()
+                .format,
+            program.format
+        );
+
+        let program = Parser::new(&b"<inSequence>\n<drop/>\n</inSequence>"[..])
+            .parse_progarm()
+            .unwrap();
+        assert_eq!(program.format, ast::SourceFormat::default());
+    }
 
     #[test]
-    fn test_in_sequence() {
+    fn test_walk() {
         let input = r#"
         <inSequence>
-            <log level="custom">
-                <property name="/validate" value="inSequence" />
-            </log>
             <log level="full" />
-      </inSequence>
+            <property name="foo" value="bar" />
+        </inSequence>
         "#;
 
-        let mut parser = Parser::new(input.as_bytes());
-        let program = parser.parse_progarm();
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        let paths: Vec<String> = program
+            .walk()
+            .iter()
+            .map(|(path, _)| path.to_string())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "inSequence",
+                "inSequence/mediator[0]",
+                "inSequence/mediator[1]"
+            ]
+        );
 
-        println!("{:?}", program);
+        let path: ast::NodePath = "inSequence/mediator[1]".parse().unwrap();
+        assert!(matches!(
+            program.find(&path),
+            Some(ast::NodeRef::Mediator(ast::Mediators::Property(_)))
+        ));
 
-        assert!(program.is_ok());
+        //repeated root sequences get unique paths
+        let input = r#"
+        <inSequence>
+            <log level="full" />
+        </inSequence>
+        <inSequence>
+            <drop />
+        </inSequence>
+        <proxy name="echo">
+            <target>
+                <endpoint>
+                    <address uri="http://localhost:8280/echo" />
+                </endpoint>
+            </target>
+        </proxy>
+        "#;
 
-        let program = program.unwrap();
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        let paths: Vec<String> = program
+            .walk()
+            .iter()
+            .map(|(path, _)| path.to_string())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "inSequence",
+                "inSequence/mediator[0]",
+                "inSequence[1]",
+                "inSequence[1]/mediator[0]",
+                "proxy[echo]",
+                "proxy[echo]/target/endpoint"
+            ]
+        );
 
-        println!("{:?}", program);
+        let path: ast::NodePath = "inSequence[1]/mediator[0]".parse().unwrap();
+        assert!(matches!(
+            program.find(&path),
+            Some(ast::NodeRef::Mediator(ast::Mediators::Drop))
+        ));
 
-        assert_eq!(program.ast_nodes.len(), 1);
+        //inline endpoints of mediators are yielded below the mediator
+        let input = r#"
+        <inSequence>
+            <call>
+                <endpoint><address uri="http://localhost:8280/call" /></endpoint>
+            </call>
+            <clone>
+                <target endpoint="stock" />
+                <target>
+                    <endpoint><address uri="http://localhost:8280/clone" /></endpoint>
+                </target>
+            </clone>
+        </inSequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        let paths: Vec<String> = program
+            .walk()
+            .iter()
+            .map(|(path, _)| path.to_string())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "inSequence",
+                "inSequence/mediator[0]",
+                "inSequence/mediator[0]/endpoint",
+                "inSequence/mediator[1]",
+                "inSequence/mediator[1]/target[1]/endpoint"
+            ]
+        );
+    }
+
+    #[derive(Debug)]
+    struct CounterMediator {
+        counter: String,
+        limit: String,
+    }
+
+    impl ast::CustomMediator for CounterMediator {
+        fn name(&self) -> &str {
+            "counter"
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    impl std::fmt::Display for CounterMediator {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(
+                f,
+                "<c:counter name=\"{}\"><limit>{}</limit></c:counter>",
+                self.counter, self.limit
+            )
+        }
     }
 
     #[test]
-    fn test_log_mediator() {
+    fn test_custom_mediator() {
         let input = r#"
-        <inSequence>
-            <log level="custom">
-                <property name="/validate" value="inSequence" />
-            </log>
+        <inSequence xmlns:c="http://example.com/counter">
+            <c:counter name="requests">
+                <limit>10</limit>
+            </c:counter>
             <log level="full" />
-            <log level="debug">
-                <property name="/validate" value="foobar" />
-            </log>
         </inSequence>
         "#;
 
         let mut parser = Parser::new(input.as_bytes());
-        let program = parser.parse_progarm();
+        assert!(parser.parse_progarm().is_err());
 
-        println!("{:?}", program);
+        let mut parser = Parser::new(input.as_bytes()).register_mediator(
+            Some("http://example.com/counter"),
+            "counter",
+            |element| {
+                Ok(Box::new(CounterMediator {
+                    counter: element.attribute("name").unwrap_or_default().to_string(),
+                    limit: element.text(),
+                }))
+            },
+        );
+        let program = parser.parse_progarm().unwrap();
 
-        assert!(program.is_ok());
+        let mediators = program.mediators();
+        assert_eq!(mediators.len(), 2);
+        assert_eq!(mediators[0].name(), "counter");
+        match mediators[0] {
+            ast::Mediators::Extension(extension) => {
+                let counter = extension
+                    .as_any()
+                    .downcast_ref::<CounterMediator>()
+                    .unwrap();
+                assert_eq!(counter.counter, "requests");
+                assert_eq!(counter.limit, "10");
+            }
+            _ => {
+                panic!("not a custom mediator");
+            }
+        }
 
-        let program = program.unwrap();
+        assert_eq!(
+            program.to_string(),
+            r#"<inSequence><c:counter name="requests"><limit>10</limit></c:counter><log level="full"></log></inSequence>"#
+        );
 
-        println!("{:?}", program);
+        //namespaced extensions take precedence over built-in mediators with the same name
+        let input = r#"
+        <inSequence xmlns:acme="urn:acme">
+            <acme:log name="audit" />
+            <log level="full" />
+        </inSequence>
+        "#;
 
-        assert_eq!(program.ast_nodes.len(), 1);
+        let mut parser = Parser::new(input.as_bytes());
+        assert!(parser.parse_progarm().is_err());
 
-        for ast_node in program.ast_nodes {
-            match ast_node {
-                ast::AstNode::Sequence(ast::Sequences::InSequence(in_sequence)) => {
-                    assert_eq!(in_sequence.mediators.len(), 3);
-                    match &in_sequence.mediators[0] {
-                        ast::Mediators::Log(log_mediator) => {
-                            assert_eq!(log_mediator.level, "custom");
-                            assert_eq!(log_mediator.properties.len(), 1);
-                            assert_eq!(log_mediator.properties[0].name, "/validate");
-                            assert_eq!(log_mediator.properties[0].value, "inSequence");
-                        }
-                        _ => {
-                            panic!("not a log mediator");
-                        }
-                    }
-                    match &in_sequence.mediators[1] {
-                        ast::Mediators::Log(log_mediator) => {
-                            assert_eq!(log_mediator.level, "full");
-                            assert_eq!(log_mediator.properties.len(), 0);
-                        }
-                        _ => {
-                            panic!("not a log mediator");
-                        }
-                    }
-                    match &in_sequence.mediators[2] {
-                        ast::Mediators::Log(log_mediator) => {
-                            assert_eq!(log_mediator.level, "debug");
-                            assert_eq!(log_mediator.properties.len(), 1);
-                            assert_eq!(log_mediator.properties[0].name, "/validate");
-                            assert_eq!(log_mediator.properties[0].value, "foobar");
-                        }
-                        _ => {
-                            panic!("not a log mediator");
-                        }
-                    }
-                }
-                _ => {
-                    panic!("not a in sequence");
-                }
-            }
-        }
+        let mut parser =
+            Parser::new(input.as_bytes()).register_mediator(Some("urn:acme"), "log", |element| {
+                Ok(Box::new(CounterMediator {
+                    counter: element.attribute("name").unwrap_or_default().to_string(),
+                    limit: element.text(),
+                }))
+            });
+        let program = parser.parse_progarm().unwrap();
+        let mediators = program.mediators();
+        assert!(matches!(mediators[0], ast::Mediators::Extension(_)));
+        assert!(matches!(mediators[1], ast::Mediators::Log(_)));
     }
 }
 
@@ -0,0 +1,246 @@
+use anyhow::Result;
+use anyhow::{bail, Context};
+
+use crate::analysis;
+use crate::ast::{self, endpoint};
+use crate::codegen;
+
+//move the inline endpoint at the given path into a named endpoint artifact, every proxy target and
+//mediator with the same inline endpoint is changed to reference the artifact by key
+pub fn hoist_endpoint(
+    program: &mut ast::Program,
+    path: &ast::NodePath,
+    name: &str,
+) -> Result<usize> {
+    let definition = program
+        .find(path)
+        .and_then(|node| analysis::inline_endpoint(path, node))
+        .with_context(|| format!("no inline endpoint at {}", path))?
+        .definition();
+
+    //inline endpoints may carry a name as well, only endpoint artifacts are referenced by key
+    let exists = program.ast_nodes.iter().any(|ast_node| {
+        matches!(ast_node, ast::AstNode::Endpoint(endpoint) if endpoint.name.as_deref() == Some(name))
+    });
+    if exists {
+        bail!("endpoint {} already exists", name);
+    }
+
+    let mut hoisted = None;
+    let mut call_sites = 0;
+    program.for_each_endpoint_mut(&mut |endpoint_ref| {
+        match endpoint_ref {
+            Some(ast::EndpointRef::Inline(endpoint)) if endpoint.definition() == definition => {}
+            _ => return,
+        }
+
+        if let Some(ast::EndpointRef::Inline(endpoint)) =
+            endpoint_ref.replace(ast::EndpointRef::Key(name.to_string()))
+        {
+            hoisted.get_or_insert(endpoint);
+            call_sites += 1;
+        }
+    });
+
+    if let Some(mut endpoint) = hoisted {
+        endpoint.name = Some(name.to_string());
+        endpoint.xmlns = Some(codegen::SYNAPSE_NAMESPACE.to_string());
+        program.ast_nodes.push(ast::AstNode::Endpoint(*endpoint));
+    }
+
+    Result::Ok(call_sites)
+}
+
+//number of api contexts and endpoint urls changed by rewrite_contexts
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RewrittenContexts {
+    pub contexts: usize,
+    pub urls: usize,
+}
+
+//add the prefix and suffix to the context of every api and insert the prefix as the first path
+//segment of every absolute endpoint url, uri-templates of resources are not changed. the suffix is
+//not added to urls: the path following the base url is the resource of the backend, appending to
+//it would call a different resource
+pub fn rewrite_contexts(
+    program: &mut ast::Program,
+    prefix: &str,
+    suffix: &str,
+) -> RewrittenContexts {
+    let mut rewritten = RewrittenContexts::default();
+
+    for ast_node in &mut program.ast_nodes {
+        match ast_node {
+            ast::AstNode::Api(api) => {
+                api.context = format!("{}{}{}", prefix, api.context, suffix);
+                rewritten.contexts += 1;
+            }
+            ast::AstNode::Endpoint(endpoint) => rewritten.urls += prefix_endpoint(endpoint, prefix),
+            _ => {}
+        }
+    }
+
+    program.for_each_endpoint_mut(&mut |endpoint_ref| {
+        if let Some(ast::EndpointRef::Inline(endpoint)) = endpoint_ref {
+            rewritten.urls += prefix_endpoint(endpoint, prefix);
+        }
+    });
+
+    rewritten
+}
+
+fn prefix_endpoint(endpoint: &mut endpoint::Endpoint, prefix: &str) -> usize {
+    let url = match &mut endpoint.kind {
+        endpoint::EndpointKind::Http(http_endpoint) => &mut http_endpoint.uri_template,
+        endpoint::EndpointKind::Address(address_endpoint) => &mut address_endpoint.uri,
+        endpoint::EndpointKind::Loadbalance(endpoint::LoadbalanceEndpoint { members, .. })
+        | endpoint::EndpointKind::Failover(endpoint::FailoverEndpoint { members, .. })
+        | endpoint::EndpointKind::RecipientList(endpoint::RecipientListEndpoint { members }) => {
+            return members
+                .iter_mut()
+                .map(|member| match member {
+                    ast::EndpointRef::Inline(endpoint) => prefix_endpoint(endpoint, prefix),
+                    ast::EndpointRef::Key(_) => 0,
+                })
+                .sum();
+        }
+        //wsdl endpoints take the address from the wsdl, default endpoints from the message
+        endpoint::EndpointKind::Wsdl(_) | endpoint::EndpointKind::Default(_) => return 0,
+    };
+
+    //urls built from variables like {uri.var.base}/orders have no known path
+    let Some(scheme_end) = url.find("://") else {
+        return 0;
+    };
+    let authority_end = url[scheme_end + 3..]
+        .find(['/', '?'])
+        .map_or(url.len(), |index| scheme_end + 3 + index);
+    url.insert_str(authority_end, prefix);
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{analysis, ast, refactor, Parser};
+
+    #[test]
+    fn test_hoist_endpoint() {
+        let input = r#"
+        <inSequence>
+            <call>
+                <endpoint name="first">
+                    <http method="GET" uri-template="http://httpbin:80/get" />
+                </endpoint>
+            </call>
+            <call>
+                <endpoint>
+                    <http method="POST" uri-template="http://httpbin:80/post" />
+                </endpoint>
+            </call>
+            <call blocking="true">
+                <endpoint>
+                    <http method="GET" uri-template="http://httpbin:80/get" />
+                </endpoint>
+            </call>
+            <iterate expression="//item">
+                <target>
+                    <endpoint>
+                        <http method="GET" uri-template="http://httpbin:80/get" />
+                    </endpoint>
+                </target>
+            </iterate>
+            <clone>
+                <target sequence="audit" />
+                <target>
+                    <endpoint>
+                        <http method="GET" uri-template="http://httpbin:80/get" />
+                    </endpoint>
+                </target>
+            </clone>
+        </inSequence>
+        "#;
+
+        let mut program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+
+        let duplicates = analysis::duplicate_endpoints(&program);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].len(), 4);
+
+        //iterate and clone targets are rewritten as well
+        let path = duplicates[0][0].clone();
+        assert_eq!(path.to_string(), "inSequence/mediator[0]/endpoint");
+        assert_eq!(
+            refactor::hoist_endpoint(&mut program, &path, "httpbin").unwrap(),
+            4
+        );
+        assert!(analysis::duplicate_endpoints(&program).is_empty());
+        assert!(refactor::hoist_endpoint(&mut program, &path, "httpbin").is_err());
+
+        let httpbin: ast::NodePath = "endpoint[httpbin]".parse().unwrap();
+        assert!(matches!(
+            program.find(&httpbin),
+            Some(ast::NodeRef::Endpoint(_))
+        ));
+        assert_eq!(
+            program
+                .to_string()
+                .matches(r#"<endpoint key="httpbin"/>"#)
+                .count(),
+            2
+        );
+        assert_eq!(
+            program.to_string().matches(r#"endpoint="httpbin""#).count(),
+            2
+        );
+        assert!(program
+            .to_string()
+            .contains(r#"<endpoint name="httpbin" xmlns="http://ws.apache.org/ns/synapse">"#));
+
+        //the serialized program references the named endpoint
+        let output = program.to_string();
+        let reparsed = Parser::new(output.as_bytes()).parse_progarm().unwrap();
+        assert_eq!(reparsed.to_string(), output);
+    }
+
+    #[test]
+    fn test_rewrite_contexts() {
+        let input = r#"
+        <api context="/orders" name="orders">
+            <resource methods="GET" uri-template="/{id}">
+                <inSequence>
+                    <call>
+                        <endpoint>
+                            <http method="GET" uri-template="http://orders:8080/orders/{uri.var.id}" />
+                        </endpoint>
+                    </call>
+                    <call>
+                        <endpoint>
+                            <failover>
+                                <endpoint><address uri="https://backup:8443?wsdl" /></endpoint>
+                                <endpoint><http uri-template="{uri.var.base}/orders" /></endpoint>
+                                <endpoint key="fallback" />
+                            </failover>
+                        </endpoint>
+                    </call>
+                </inSequence>
+            </resource>
+        </api>
+        "#;
+
+        let mut program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        assert_eq!(
+            refactor::rewrite_contexts(&mut program, "/acme", "/v1"),
+            refactor::RewrittenContexts {
+                contexts: 1,
+                urls: 2
+            }
+        );
+
+        let output = program.to_string();
+        assert!(output.contains(r#"context="/acme/orders/v1""#));
+        assert!(output.contains(r#"uri-template="/{id}""#));
+        assert!(output.contains(r#"uri-template="http://orders:8080/acme/orders/{uri.var.id}""#));
+        assert!(output.contains(r#"uri="https://backup:8443/acme?wsdl""#));
+        assert!(output.contains(r#"uri-template="{uri.var.base}/orders""#));
+    }
+}
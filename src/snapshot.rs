@@ -0,0 +1,105 @@
+use anyhow::Result;
+use anyhow::{bail, Context};
+use std::fs;
+use std::path::Path;
+
+use crate::Parser;
+
+//environment variable which overwrites existing snapshots instead of comparing them
+pub const UPDATE_ENV: &str = "UPDATE_SNAPSHOTS";
+
+//debug representation of the parsed input as stored in a snapshot
+pub fn snapshot(input: &[u8]) -> Result<String> {
+    let program = Parser::new(input).parse_progarm()?;
+    Result::Ok(format!("{:#?}\n", program))
+}
+
+//parse every .xml file of the directory and compare the result with the .snap file next to it,
+//missing or outdated snapshots are written to .snap.new and reported unless UPDATE_SNAPSHOTS is set
+pub fn assert_snapshots(dir: impl AsRef<Path>) -> Result<()> {
+    check_snapshots(dir.as_ref(), std::env::var_os(UPDATE_ENV).is_some())
+}
+
+fn check_snapshots(dir: &Path, update: bool) -> Result<()> {
+    let mut mismatches = Vec::new();
+
+    let mut inputs: Vec<_> = fs::read_dir(dir)
+        .with_context(|| format!("error reading {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "xml"))
+        .collect();
+    inputs.sort();
+
+    for input in inputs {
+        let actual = snapshot(&fs::read(&input)?)
+            .with_context(|| format!("error parsing {}", input.display()))?;
+        let snapshot = input.with_extension("snap");
+        let pending = input.with_extension("snap.new");
+
+        match fs::read_to_string(&snapshot) {
+            Result::Ok(expected) if expected == actual => {}
+            _ if update => fs::write(&snapshot, actual)?,
+            Result::Ok(_) => {
+                fs::write(&pending, actual)?;
+                mismatches.push(format!("{} (outdated)", input.display()));
+                continue;
+            }
+            Result::Err(_) => {
+                fs::write(&pending, actual)?;
+                mismatches.push(format!("{} (missing)", input.display()));
+                continue;
+            }
+        }
+        if pending.exists() {
+            fs::remove_file(&pending)?;
+        }
+    }
+
+    if !mismatches.is_empty() {
+        bail!(
+            "snapshots do not match for: {} (review the .snap.new files or set {} to update them)",
+            mismatches.join(", "),
+            UPDATE_ENV
+        );
+    }
+
+    Result::Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::snapshot;
+
+    #[test]
+    fn test_assert_snapshots() {
+        let dir = std::env::temp_dir().join(format!("synapse-snapshots-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("sequence.xml"),
+            r#"<inSequence><log level="full" /></inSequence>"#,
+        )
+        .unwrap();
+
+        //a missing snapshot fails and is written to .snap.new for review
+        assert!(snapshot::check_snapshots(&dir, false).is_err());
+        assert!(!dir.join("sequence.snap").exists());
+        assert!(dir.join("sequence.snap.new").exists());
+
+        //updating writes the snapshot, later runs compare against it
+        snapshot::check_snapshots(&dir, true).unwrap();
+        assert!(dir.join("sequence.snap").exists());
+        assert!(!dir.join("sequence.snap.new").exists());
+        snapshot::check_snapshots(&dir, false).unwrap();
+
+        fs::write(dir.join("sequence.snap"), "outdated").unwrap();
+        assert!(snapshot::check_snapshots(&dir, false).is_err());
+        assert_eq!(
+            fs::read_to_string(dir.join("sequence.snap")).unwrap(),
+            "outdated"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
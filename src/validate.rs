@@ -0,0 +1,1110 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use crate::ast::endpoint;
+use crate::catalog::{self, FunctionInfo, Runtime};
+use crate::{analysis, ast, error_codes};
+
+#[derive(Debug, PartialEq)]
+pub struct UnsupportedFeature {
+    pub element: &'static str,
+    pub since: Runtime,
+    pub deprecated_since: Option<Runtime>,
+}
+
+//report every mediator of the program that is not yet or no longer available on the given runtime.
+//only the mediator elements are compared, the catalog does not record in which runtime an attribute
+//or child element was added, so these are not checked
+pub fn unsupported_features(program: &ast::Program, runtime: Runtime) -> Vec<UnsupportedFeature> {
+    program
+        .mediators()
+        .into_iter()
+        .filter_map(|mediator| catalog::mediator(mediator.name()))
+        .filter(|info| {
+            info.since > runtime
+                || info
+                    .deprecated_since
+                    .is_some_and(|deprecated_since| deprecated_since <= runtime)
+        })
+        .map(|info| UnsupportedFeature {
+            element: info.name,
+            since: info.since,
+            deprecated_since: info.deprecated_since,
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+pub struct UnknownContextProperty {
+    pub path: ast::NodePath,
+    pub expression: String,
+    pub property: String,
+}
+
+//report expressions reading synapse or axis2 properties which are neither set by the runtime nor
+//earlier in the same flow, a flow is an api resource, a proxy, a root sequence or the root
+//mediators of a fragment. properties count as set when a property mediator of the same scope
+//sets them before the read in document order, in any branch, or in a named sequence invoked
+//before the read. named sequences start with the properties set by all their callers
+pub fn unknown_context_properties(program: &ast::Program) -> Vec<UnknownContextProperty> {
+    let flows = flows(program);
+
+    //properties set by each named sequence, including the sequences it invokes
+    let mut summaries: BTreeMap<&str, BTreeSet<analysis::Read>> = BTreeMap::new();
+    for flow in &flows {
+        if let Some(name) = flow.sequence {
+            let mut visited = BTreeSet::new();
+            summaries.insert(name, sequence_sets(name, &flows, &mut visited));
+        }
+    }
+
+    //properties set before each invocation of a named sequence, these only grow so the loop ends
+    let mut entries: BTreeMap<&str, BTreeSet<analysis::Read>> = BTreeMap::new();
+    loop {
+        let mut changed = false;
+        for flow in &flows {
+            let entry = flow_entry(flow, &entries);
+            for (name, set) in check_flow(flow, entry, &summaries, &mut Vec::new()) {
+                let known = entries.entry(name).or_default();
+                let before = known.len();
+                known.extend(set);
+                changed |= known.len() != before;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut unknown = Vec::new();
+    for flow in &flows {
+        check_flow(flow, flow_entry(flow, &entries), &summaries, &mut unknown);
+    }
+    unknown
+}
+
+struct Flow<'a> {
+    //name of the named sequence the flow belongs to
+    sequence: Option<&'a str>,
+    mediators: Vec<(ast::NodePath, &'a ast::Mediators)>,
+}
+
+fn flows(program: &ast::Program) -> Vec<Flow<'_>> {
+    let mut flows: Vec<Flow> = Vec::new();
+    let mut root_mediators = false;
+
+    for (path, node) in program.walk() {
+        let root = path.segments.len() == 1;
+        match node {
+            ast::NodeRef::Mediator(mediator) if root && root_mediators => {
+                if let Some(flow) = flows.last_mut() {
+                    flow.mediators.push((path, mediator));
+                }
+            }
+            ast::NodeRef::Mediator(mediator) if root => {
+                root_mediators = true;
+                flows.push(Flow {
+                    sequence: None,
+                    mediators: vec![(path, mediator)],
+                });
+            }
+            ast::NodeRef::Mediator(mediator) => {
+                if let Some(flow) = flows.last_mut() {
+                    flow.mediators.push((path, mediator));
+                }
+            }
+            ast::NodeRef::Sequence(ast::Sequences::NamedSequence(named_sequence)) if root => {
+                root_mediators = false;
+                flows.push(Flow {
+                    sequence: Some(&named_sequence.name),
+                    mediators: Vec::new(),
+                });
+            }
+            ast::NodeRef::Resource(_) => flows.push(Flow {
+                sequence: None,
+                mediators: Vec::new(),
+            }),
+            _ if root => {
+                root_mediators = false;
+                flows.push(Flow {
+                    sequence: None,
+                    mediators: Vec::new(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    flows
+}
+
+//property set by the mediator, keyed like the reads of expressions
+fn set_property(mediator: &ast::Mediators) -> Option<analysis::Read<'_>> {
+    match analysis::written(mediator)? {
+        (analysis::Usage::Set, read) => Some(read),
+        _ => None,
+    }
+}
+
+//key of the named sequence invoked by a sequence mediator
+fn invoked_sequence(mediator: &ast::Mediators) -> Option<&str> {
+    match mediator {
+        ast::Mediators::Sequence(sequence) => Some(&sequence.key),
+        _ => None,
+    }
+}
+
+fn sequence_sets<'a>(
+    name: &'a str,
+    flows: &[Flow<'a>],
+    visited: &mut BTreeSet<&'a str>,
+) -> BTreeSet<analysis::Read<'a>> {
+    let mut sets = BTreeSet::new();
+    if !visited.insert(name) {
+        return sets;
+    }
+    for flow in flows.iter().filter(|flow| flow.sequence == Some(name)) {
+        for (_, mediator) in &flow.mediators {
+            sets.extend(set_property(mediator));
+            if let Some(invoked) = invoked_sequence(mediator) {
+                sets.extend(sequence_sets(invoked, flows, visited));
+            }
+        }
+    }
+    sets
+}
+
+fn flow_entry<'a>(
+    flow: &Flow<'a>,
+    entries: &BTreeMap<&'a str, BTreeSet<analysis::Read<'a>>>,
+) -> BTreeSet<analysis::Read<'a>> {
+    flow.sequence
+        .and_then(|name| entries.get(name))
+        .cloned()
+        .unwrap_or_default()
+}
+
+//report the unknown reads of the flow and return the properties set before each invocation of
+//a named sequence
+fn check_flow<'a>(
+    flow: &Flow<'a>,
+    mut set: BTreeSet<analysis::Read<'a>>,
+    summaries: &BTreeMap<&str, BTreeSet<analysis::Read<'a>>>,
+    unknown: &mut Vec<UnknownContextProperty>,
+) -> Vec<(&'a str, BTreeSet<analysis::Read<'a>>)> {
+    let mut invocations = Vec::new();
+
+    for (path, mediator) in &flow.mediators {
+        for expression in mediator.expressions() {
+            for read in analysis::reads(expression) {
+                let (runtime_properties, property) = match read {
+                    analysis::Read::Property(property) => (catalog::SYNAPSE_PROPERTIES, property),
+                    analysis::Read::Axis2Property(property) => {
+                        (catalog::AXIS2_PROPERTIES, property)
+                    }
+                    _ => continue,
+                };
+                if runtime_properties.contains(&property) || set.contains(&read) {
+                    continue;
+                }
+                unknown.push(UnknownContextProperty {
+                    path: path.clone(),
+                    expression: expression.to_string(),
+                    property: property.to_string(),
+                });
+            }
+        }
+
+        set.extend(set_property(mediator));
+        if let Some(invoked) = invoked_sequence(mediator) {
+            invocations.push((invoked, set.clone()));
+            if let Some(sets) = summaries.get(invoked) {
+                set.extend(sets.iter().copied());
+            }
+        }
+    }
+
+    invocations
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FunctionCallError {
+    Unknown,
+    //number of arguments of the call
+    Arguments(usize),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InvalidFunctionCall {
+    pub path: ast::NodePath,
+    pub expression: String,
+    pub function: String,
+    pub error: FunctionCallError,
+}
+
+//report calls of functions which are neither builtin nor custom and calls with a wrong number of
+//arguments, custom functions are e.g. extension functions registered with the runtime and take
+//precedence over builtin functions with the same name
+pub fn invalid_function_calls(
+    program: &ast::Program,
+    custom: &[FunctionInfo<'_>],
+) -> Vec<InvalidFunctionCall> {
+    let mut invalid = Vec::new();
+
+    for (path, node) in program.walk() {
+        let ast::NodeRef::Mediator(mediator) = node else {
+            continue;
+        };
+
+        for expression in mediator.expressions() {
+            for (function, args) in analysis::function_calls(expression) {
+                let info = custom
+                    .iter()
+                    .find(|info| info.name == function)
+                    .or_else(|| catalog::function(function));
+                let error = match info {
+                    Some(info) if info.accepts(args) => continue,
+                    Some(_) => FunctionCallError::Arguments(args),
+                    None => FunctionCallError::Unknown,
+                };
+                invalid.push(InvalidFunctionCall {
+                    path: path.clone(),
+                    expression: expression.to_string(),
+                    function: function.to_string(),
+                    error,
+                });
+            }
+        }
+    }
+
+    invalid
+}
+
+#[derive(Debug, PartialEq)]
+pub enum EndpointIssue {
+    //the runtime ignores a zero timeout and falls back to the global timeout
+    ZeroTimeout,
+    //initial and maximum duration of zero with a factor of 1.0 never suspend the endpoint
+    SuspensionDisabled,
+    //error codes listed by suspendOnFailure and markForSuspension
+    OverlappingErrorCodes(Vec<i32>),
+}
+
+impl Display for EndpointIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EndpointIssue::ZeroTimeout => write!(
+                f,
+                "a timeout duration of 0 is ignored, the global timeout of the runtime applies"
+            ),
+            EndpointIssue::SuspensionDisabled => write!(
+                f,
+                "suspendOnFailure with initialDuration 0, progressionFactor 1.0 and maximumDuration 0 never suspends the endpoint"
+            ),
+            EndpointIssue::OverlappingErrorCodes(codes) => {
+                let codes: Vec<String> = codes.iter().map(i32::to_string).collect();
+                write!(
+                    f,
+                    "error codes {} suspend the endpoint right away, the retries of markForSuspension never apply to them",
+                    codes.join(", ")
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct EndpointWarning {
+    pub path: ast::NodePath,
+    pub issue: EndpointIssue,
+}
+
+//report suspicious timeout and suspension settings of endpoint artifacts and inline endpoints,
+//members of group endpoints are addressed as member[index] below the group
+pub fn endpoint_warnings(program: &ast::Program) -> Vec<EndpointWarning> {
+    let mut warnings = Vec::new();
+    for (path, node) in program.walk() {
+        if let ast::NodeRef::Endpoint(endpoint) = node {
+            check_endpoint(&path, endpoint, &mut warnings);
+        }
+    }
+    warnings
+}
+
+fn check_endpoint(
+    path: &ast::NodePath,
+    endpoint: &endpoint::Endpoint,
+    warnings: &mut Vec<EndpointWarning>,
+) {
+    let mut warn = |issue| {
+        warnings.push(EndpointWarning {
+            path: path.clone(),
+            issue,
+        })
+    };
+
+    if let Some(qos) = endpoint.qos() {
+        if let Some(endpoint::Timeout {
+            duration: Some(0), ..
+        }) = qos.timeout
+        {
+            warn(EndpointIssue::ZeroTimeout);
+        }
+
+        if let Some(suspend_on_failure) = &qos.suspend_on_failure {
+            //the runtime defaults the progression factor to 1.0
+            if suspend_on_failure.initial_duration == Some(0)
+                && suspend_on_failure.progression_factor.unwrap_or(1.0) == 1.0
+                && suspend_on_failure.maximum_duration == Some(0)
+            {
+                warn(EndpointIssue::SuspensionDisabled);
+            }
+
+            if let Some(mark_for_suspension) = &qos.mark_for_suspension {
+                let overlapping: Vec<i32> = suspend_on_failure
+                    .error_codes
+                    .iter()
+                    .copied()
+                    .filter(|code| {
+                        *code != error_codes::DISABLED
+                            && mark_for_suspension.error_codes.contains(code)
+                    })
+                    .collect();
+                if !overlapping.is_empty() {
+                    warn(EndpointIssue::OverlappingErrorCodes(overlapping));
+                }
+            }
+        }
+    }
+
+    for (index, member) in endpoint.members().iter().enumerate() {
+        if let ast::EndpointRef::Inline(member) = member {
+            check_endpoint(&path.child(format!("member[{}]", index)), member, warnings);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MissingTimeout {
+    //path of the call or send mediator
+    pub path: ast::NodePath,
+    //path of the leaf endpoint without timeout, endpoints referenced by key are resolved to the
+    //endpoint artifact of the program
+    pub endpoint: ast::NodePath,
+}
+
+//report call and send mediators sending to a leaf endpoint without timeout, these wait for the
+//global timeout of the runtime. keys of endpoints which are not part of the program (e.g. registry
+//resources) are not resolved
+pub fn missing_timeouts(program: &ast::Program) -> Vec<MissingTimeout> {
+    let artifacts: BTreeMap<&str, &endpoint::Endpoint> = program
+        .ast_nodes
+        .iter()
+        .filter_map(|ast_node| match ast_node {
+            ast::AstNode::Endpoint(endpoint) => Some((endpoint.name.as_deref()?, endpoint)),
+            _ => None,
+        })
+        .collect();
+
+    let mut missing = Vec::new();
+    for (path, node) in program.walk() {
+        let endpoint_ref = match node {
+            ast::NodeRef::Mediator(ast::Mediators::Call(ast::CallMediator {
+                endpoint: Some(endpoint_ref),
+                ..
+            }))
+            | ast::NodeRef::Mediator(ast::Mediators::Send(ast::SendMediator {
+                endpoint: Some(endpoint_ref),
+                ..
+            })) => endpoint_ref,
+            _ => continue,
+        };
+
+        let mut untimed = Vec::new();
+        untimed_endpoints(
+            &path.child("endpoint"),
+            endpoint_ref,
+            &artifacts,
+            &mut BTreeSet::new(),
+            &mut untimed,
+        );
+        missing.extend(untimed.into_iter().map(|endpoint| MissingTimeout {
+            path: path.clone(),
+            endpoint,
+        }));
+    }
+    missing
+}
+
+fn untimed_endpoints<'a>(
+    path: &ast::NodePath,
+    endpoint_ref: &'a ast::EndpointRef,
+    artifacts: &BTreeMap<&str, &'a endpoint::Endpoint>,
+    visited: &mut BTreeSet<&'a str>,
+    untimed: &mut Vec<ast::NodePath>,
+) {
+    let (path, endpoint) = match endpoint_ref {
+        //groups referencing each other are resolved once
+        ast::EndpointRef::Key(key) => match artifacts.get(key.as_str()) {
+            Some(endpoint) if visited.insert(key) => (
+                ast::NodePath::default().child(format!("endpoint[{}]", key)),
+                *endpoint,
+            ),
+            _ => return,
+        },
+        ast::EndpointRef::Inline(endpoint) => (path.clone(), endpoint.as_ref()),
+    };
+
+    match endpoint.qos() {
+        Some(qos) if qos.timeout.is_none() => untimed.push(path),
+        Some(_) => {}
+        None => {
+            for (index, member) in endpoint.members().iter().enumerate() {
+                let member_path = path.child(format!("member[{}]", index));
+                untimed_endpoints(&member_path, member, artifacts, visited, untimed);
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FlowIssue {
+    //the mediator follows a respond, drop or loopback and never runs
+    Unreachable,
+    //the in sequence can reach its end without responding to the client
+    NoResponse,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FlowWarning {
+    pub path: ast::NodePath,
+    pub issue: FlowIssue,
+}
+
+//report mediators after a respond, drop or loopback and in sequences of api resources and proxies
+//without endpoint which can end without responding. filter and switch respond when all their
+//branches respond, named sequences of the program are followed and sequences which are not part
+//of the program are assumed to respond. iterate and clone hand the message to their targets whose
+//responses are collected elsewhere, they count as responding as well
+pub fn flow_warnings(program: &ast::Program) -> Vec<FlowWarning> {
+    let named: BTreeMap<&str, &[ast::Mediators]> = program
+        .ast_nodes
+        .iter()
+        .filter_map(|ast_node| match ast_node {
+            ast::AstNode::Sequence(ast::Sequences::NamedSequence(named_sequence)) => Some((
+                named_sequence.name.as_str(),
+                named_sequence.mediators.as_slice(),
+            )),
+            _ => None,
+        })
+        .collect();
+
+    let mut warnings = Vec::new();
+    for (path, node) in program.walk() {
+        match node {
+            ast::NodeRef::Sequence(sequence) => {
+                unreachable_mediators(&path, sequence.mediators(), &mut warnings)
+            }
+            ast::NodeRef::Mediator(mediator) => {
+                for (name, branch) in mediator.branches() {
+                    unreachable_mediators(&path.child(name), branch, &mut warnings);
+                }
+            }
+            _ => {}
+        }
+
+        let in_sequence = match node {
+            ast::NodeRef::Resource(resource) => resource
+                .in_sequence()
+                .map(|in_sequence| (path.child("inSequence"), in_sequence)),
+            ast::NodeRef::Proxy(ast::Proxy {
+                target: Some(target),
+                ..
+            }) if target.endpoint.is_none() => {
+                target.sequences.iter().find_map(|sequence| match sequence {
+                    ast::Sequences::InSequence(in_sequence) => {
+                        Some((path.child("target").child("inSequence"), in_sequence))
+                    }
+                    _ => None,
+                })
+            }
+            _ => None,
+        };
+        if let Some((path, in_sequence)) = in_sequence {
+            if !responds(&in_sequence.mediators, &named, &mut BTreeSet::new()) {
+                warnings.push(FlowWarning {
+                    path,
+                    issue: FlowIssue::NoResponse,
+                });
+            }
+        }
+    }
+    warnings
+}
+
+fn unreachable_mediators(
+    parent: &ast::NodePath,
+    mediators: &[ast::Mediators],
+    warnings: &mut Vec<FlowWarning>,
+) {
+    let Some(end) = mediators.iter().position(ends_flow) else {
+        return;
+    };
+    for index in end + 1..mediators.len() {
+        warnings.push(FlowWarning {
+            path: parent.child(format!("mediator[{}]", index)),
+            issue: FlowIssue::Unreachable,
+        });
+    }
+}
+
+//mediators following the mediator in the same sequence never run
+fn ends_flow(mediator: &ast::Mediators) -> bool {
+    let ends = |mediators: &[ast::Mediators]| mediators.iter().any(ends_flow);
+    match mediator {
+        ast::Mediators::Respond | ast::Mediators::Drop | ast::Mediators::Loopback => true,
+        ast::Mediators::Filter(filter) => filter
+            .else_mediators
+            .as_ref()
+            .is_some_and(|else_mediators| ends(&filter.then_mediators) && ends(else_mediators)),
+        ast::Mediators::Switch(switch) => switch.default.as_ref().is_some_and(|default| {
+            ends(default) && switch.cases.iter().all(|case| ends(&case.mediators))
+        }),
+        _ => false,
+    }
+}
+
+fn responds<'a>(
+    mediators: &'a [ast::Mediators],
+    named: &BTreeMap<&str, &'a [ast::Mediators]>,
+    visited: &mut BTreeSet<&'a str>,
+) -> bool {
+    mediators.iter().any(|mediator| match mediator {
+        //drop ends the flow on purpose, e.g. for out only messages
+        ast::Mediators::Respond
+        | ast::Mediators::Send(_)
+        | ast::Mediators::Loopback
+        | ast::Mediators::Drop
+        | ast::Mediators::Iterate(_)
+        | ast::Mediators::Clone(_) => true,
+        ast::Mediators::Filter(filter) => {
+            filter
+                .else_mediators
+                .as_ref()
+                .is_some_and(|else_mediators| {
+                    responds(&filter.then_mediators, named, visited)
+                        && responds(else_mediators, named, visited)
+                })
+        }
+        ast::Mediators::Switch(switch) => switch.default.as_ref().is_some_and(|default| {
+            responds(default, named, visited)
+                && switch
+                    .cases
+                    .iter()
+                    .all(|case| responds(&case.mediators, named, visited))
+        }),
+        ast::Mediators::Sequence(sequence) => match named.get(sequence.key.as_str()) {
+            //recursive sequences are followed once
+            Some(mediators) => visited.insert(&sequence.key) && responds(mediators, named, visited),
+            None => true,
+        },
+        _ => false,
+    })
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MissingClass {
+    //path of the class mediator, or of the api for handlers
+    pub path: ast::NodePath,
+    pub class: String,
+}
+
+//report class mediators and api handlers whose class is not in the given list of fully qualified
+//class names, e.g. the classes of the jars bundled with the project
+pub fn missing_classes(program: &ast::Program, available: &[&str]) -> Vec<MissingClass> {
+    let mut missing = Vec::new();
+    for (path, node) in program.walk() {
+        let classes: Vec<&str> = match node {
+            ast::NodeRef::Mediator(ast::Mediators::Class(class_mediator)) => {
+                vec![&class_mediator.name]
+            }
+            ast::NodeRef::Api(api) => api
+                .handlers
+                .iter()
+                .map(|handler| handler.class.as_str())
+                .collect(),
+            _ => continue,
+        };
+        for class in classes {
+            if !available.contains(&class) {
+                missing.push(MissingClass {
+                    path: path.clone(),
+                    class: class.to_string(),
+                });
+            }
+        }
+    }
+    missing
+}
+
+//fully qualified class names of a jar listing like the output of jar tf, one entry per line
+pub fn listed_classes(listing: &str) -> Vec<String> {
+    listing
+        .lines()
+        .filter_map(|entry| entry.trim().strip_suffix(".class"))
+        .filter(|class| !class.ends_with("module-info") && !class.ends_with("package-info"))
+        .map(|class| class.replace('/', "."))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::catalog::{ArgType, FunctionInfo, Runtime};
+    use crate::validate::{
+        EndpointIssue, EndpointWarning, FlowIssue, FlowWarning, FunctionCallError, MissingClass,
+        MissingTimeout,
+    };
+    use crate::{validate, Parser};
+
+    #[test]
+    fn test_unsupported_features() {
+        let input = r#"
+        <inSequence>
+            <log level="custom">
+                <property name="/validate" value="inSequence" />
+            </log>
+            <property name="foo" value="bar" />
+            <filter xpath="$ctx:foo">
+                <variable name="ORDER_ID" expression="json-eval($.order.id)" type="STRING" />
+            </filter>
+        </inSequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+
+        assert_eq!(program.mediators().len(), 4);
+        assert_eq!(
+            validate::unsupported_features(&program, Runtime::Ei660),
+            vec![validate::UnsupportedFeature {
+                element: "variable",
+                since: Runtime::Mi440,
+                deprecated_since: None,
+            }]
+        );
+        assert_eq!(
+            validate::unsupported_features(&program, Runtime::Mi410).len(),
+            1
+        );
+        assert!(validate::unsupported_features(&program, Runtime::Mi440).is_empty());
+
+        //mediators dropped by newer runtimes are reported from the deprecating runtime on
+        let input = r#"<inSequence><spring bean="orders" key="conf:/spring.xml"/></inSequence>"#;
+        let deprecated = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        assert!(validate::unsupported_features(&deprecated, Runtime::Ei660).is_empty());
+        assert_eq!(
+            validate::unsupported_features(&deprecated, Runtime::Mi410),
+            vec![validate::UnsupportedFeature {
+                element: "spring",
+                since: Runtime::Ei660,
+                deprecated_since: Some(Runtime::Mi410),
+            }]
+        );
+
+        //mediators of newer runtimes are written back as they were read
+        assert_eq!(
+            program.mediators()[3].to_string(),
+            r#"<variable name="ORDER_ID" expression="json-eval($.order.id)" type="STRING"/>"#
+        );
+    }
+
+    #[test]
+    fn test_unknown_context_properties() {
+        let input = r#"
+        <inSequence>
+            <property name="ORDER_ID" expression="json-eval($.order.id)" />
+            <log level="custom" category="ERROR">
+                <property name="HTTP_SC" expression="$axis2:HTTP_SC" />
+                <property name="ERROR_MESSAGE" expression="$ctx:ERROR_MESSAGE" />
+                <property name="ORDER" expression="get-property('ORDER_ID')" />
+                <property name="CUSTOMER" expression="$ctx:CUSTOMER_ID" />
+                <property name="STATUS" expression="get-property('axis2', 'STATUS')" />
+            </log>
+        </inSequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+
+        let unknown = validate::unknown_context_properties(&program);
+        assert_eq!(unknown.len(), 2);
+        assert_eq!(unknown[0].path.to_string(), "inSequence/mediator[1]");
+        assert_eq!(unknown[0].property, "CUSTOMER_ID");
+        assert_eq!(unknown[1].property, "STATUS");
+    }
+
+    #[test]
+    fn test_unknown_context_properties_in_flow() {
+        let input = r#"
+        <api context="/orders" name="orders">
+            <resource methods="POST">
+                <inSequence>
+                    <log level="custom">
+                        <property name="early" expression="$ctx:ORDER_ID" />
+                    </log>
+                    <property name="ORDER_ID" expression="json-eval($.order.id)" />
+                    <property name="STATUS" value="accepted" scope="axis2" />
+                    <sequence key="SetTenant" />
+                    <log level="custom">
+                        <property name="order" expression="$ctx:ORDER_ID" />
+                        <property name="status" expression="$ctx:STATUS" />
+                        <property name="tenant" expression="$ctx:TENANT" />
+                    </log>
+                </inSequence>
+            </resource>
+            <resource methods="GET">
+                <inSequence>
+                    <log level="custom">
+                        <property name="order" expression="$ctx:ORDER_ID" />
+                    </log>
+                </inSequence>
+            </resource>
+        </api>
+        <sequence name="SetTenant">
+            <property name="TENANT" value="acme" />
+            <log level="custom">
+                <property name="order" expression="$ctx:ORDER_ID" />
+            </log>
+        </sequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+
+        let unknown: Vec<(String, String)> = validate::unknown_context_properties(&program)
+            .into_iter()
+            .map(|unknown| (unknown.path.to_string(), unknown.property))
+            .collect();
+        assert_eq!(
+            unknown,
+            vec![
+                //read before it is set
+                (
+                    "api[orders]/resource[0]/inSequence/mediator[0]".to_string(),
+                    "ORDER_ID".to_string()
+                ),
+                //set in the axis2 scope only
+                (
+                    "api[orders]/resource[0]/inSequence/mediator[4]".to_string(),
+                    "STATUS".to_string()
+                ),
+                //set in another resource only
+                (
+                    "api[orders]/resource[1]/inSequence/mediator[0]".to_string(),
+                    "ORDER_ID".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_function_calls() {
+        let input = r#"
+        <inSequence>
+            <property name="TOTAL" expression="round(sum(//item/price))" />
+            <property name="DISCOUNT" expression="acme:discount($ctx:TOTAL, 'gold')" />
+            <filter xpath="fn:starts-with(get-property('transport', 'X-Tenant'))">
+                <log level="custom">
+                    <property name="tenant" expression="tenant-name()" />
+                </log>
+            </filter>
+        </inSequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+
+        let invalid = validate::invalid_function_calls(&program, &[]);
+        let found: Vec<(&str, &FunctionCallError)> = invalid
+            .iter()
+            .map(|call| (call.function.as_str(), &call.error))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("acme:discount", &FunctionCallError::Unknown),
+                ("starts-with", &FunctionCallError::Arguments(1)),
+                ("tenant-name", &FunctionCallError::Unknown),
+            ]
+        );
+        assert_eq!(invalid[1].path.to_string(), "inSequence/mediator[2]");
+
+        let custom = [
+            FunctionInfo {
+                name: "acme:discount",
+                args: &[ArgType::Number, ArgType::String],
+                min_args: 2,
+                variadic: false,
+            },
+            FunctionInfo {
+                name: "tenant-name",
+                args: &[],
+                min_args: 0,
+                variadic: false,
+            },
+        ];
+        let invalid = validate::invalid_function_calls(&program, &custom);
+        assert_eq!(invalid.len(), 1);
+        assert_eq!(invalid[0].function, "starts-with");
+    }
+
+    #[test]
+    fn test_endpoint_warnings() {
+        let input = r#"
+        <endpoint name="orders">
+            <http uri-template="http://orders:8080/orders">
+                <timeout><duration>0</duration></timeout>
+            </http>
+        </endpoint>
+        <inSequence>
+            <call>
+                <endpoint>
+                    <failover>
+                        <endpoint key="orders" />
+                        <endpoint>
+                            <address uri="http://backup:8080/orders">
+                                <timeout><duration>0</duration></timeout>
+                            </address>
+                        </endpoint>
+                    </failover>
+                </endpoint>
+            </call>
+            <call>
+                <endpoint>
+                    <address uri="http://stock:8080/stock">
+                        <timeout><duration>30000</duration></timeout>
+                    </address>
+                </endpoint>
+            </call>
+        </inSequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        assert_eq!(
+            validate::endpoint_warnings(&program),
+            vec![
+                EndpointWarning {
+                    path: "endpoint[orders]".parse().unwrap(),
+                    issue: EndpointIssue::ZeroTimeout,
+                },
+                EndpointWarning {
+                    path: "inSequence/mediator[0]/endpoint/member[1]".parse().unwrap(),
+                    issue: EndpointIssue::ZeroTimeout,
+                },
+            ]
+        );
+
+        //suspension settings which do not do what they seem to
+        let input = r#"
+        <endpoint name="orders">
+            <address uri="http://orders:8080/orders">
+                <suspendOnFailure>
+                    <errorCodes>101504, 101505</errorCodes>
+                    <initialDuration>0</initialDuration>
+                    <maximumDuration>0</maximumDuration>
+                </suspendOnFailure>
+                <markForSuspension>
+                    <errorCodes>101503, 101504</errorCodes>
+                    <retriesBeforeSuspension>3</retriesBeforeSuspension>
+                </markForSuspension>
+            </address>
+        </endpoint>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        let warnings = validate::endpoint_warnings(&program);
+        assert_eq!(
+            warnings
+                .iter()
+                .map(|warning| &warning.issue)
+                .collect::<Vec<_>>(),
+            vec![
+                &EndpointIssue::SuspensionDisabled,
+                &EndpointIssue::OverlappingErrorCodes(vec![101504])
+            ]
+        );
+        assert_eq!(
+            warnings[1].issue.to_string(),
+            "error codes 101504 suspend the endpoint right away, the retries of markForSuspension never apply to them"
+        );
+    }
+
+    #[test]
+    fn test_missing_timeouts() {
+        let input = r#"
+        <endpoint name="orders">
+            <http uri-template="http://orders:8080/orders" />
+        </endpoint>
+        <endpoint name="stock">
+            <http uri-template="http://stock:8080/stock">
+                <timeout><duration>30000</duration></timeout>
+            </http>
+        </endpoint>
+        <inSequence>
+            <call>
+                <endpoint key="orders" />
+            </call>
+            <call>
+                <endpoint key="stock" />
+            </call>
+            <send>
+                <endpoint>
+                    <loadbalance>
+                        <endpoint key="stock" />
+                        <endpoint><address uri="http://backup:8080/stock" /></endpoint>
+                    </loadbalance>
+                </endpoint>
+            </send>
+            <call>
+                <endpoint key="conf:/endpoints/registry.xml" />
+            </call>
+        </inSequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        assert_eq!(
+            validate::missing_timeouts(&program),
+            vec![
+                MissingTimeout {
+                    path: "inSequence/mediator[0]".parse().unwrap(),
+                    endpoint: "endpoint[orders]".parse().unwrap(),
+                },
+                MissingTimeout {
+                    path: "inSequence/mediator[2]".parse().unwrap(),
+                    endpoint: "inSequence/mediator[2]/endpoint/member[1]".parse().unwrap(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flow_warnings() {
+        let input = r#"
+        <api context="/orders" name="orders">
+            <resource methods="GET" uri-template="/{id}">
+                <inSequence>
+                    <filter xpath="$ctx:id">
+                        <then><respond /></then>
+                        <else><drop /></else>
+                    </filter>
+                    <log level="full" />
+                </inSequence>
+            </resource>
+            <resource methods="POST" uri-template="/">
+                <inSequence>
+                    <call><endpoint key="orders" /></call>
+                    <filter xpath="$ctx:created">
+                        <then><sequence key="reply" /></then>
+                    </filter>
+                </inSequence>
+            </resource>
+            <resource methods="PUT" uri-template="/{id}">
+                <inSequence>
+                    <call><endpoint key="orders" /></call>
+                    <sequence key="reply" />
+                </inSequence>
+            </resource>
+        </api>
+        <sequence name="reply">
+            <respond />
+            <log level="full" />
+        </sequence>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        assert_eq!(
+            validate::flow_warnings(&program),
+            vec![
+                FlowWarning {
+                    path: "api[orders]/resource[0]/inSequence/mediator[1]"
+                        .parse()
+                        .unwrap(),
+                    issue: FlowIssue::Unreachable,
+                },
+                FlowWarning {
+                    path: "api[orders]/resource[1]/inSequence".parse().unwrap(),
+                    issue: FlowIssue::NoResponse,
+                },
+                FlowWarning {
+                    path: "sequence[reply]/mediator[1]".parse().unwrap(),
+                    issue: FlowIssue::Unreachable,
+                },
+            ]
+        );
+
+        //proxies with a target endpoint send the message after the in sequence
+        let input = r#"
+        <proxy name="forward">
+            <target>
+                <inSequence><log level="full" /></inSequence>
+                <endpoint key="orders" />
+            </target>
+        </proxy>
+        <proxy name="silent">
+            <target>
+                <inSequence><log level="full" /></inSequence>
+            </target>
+        </proxy>
+        "#;
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        assert_eq!(
+            validate::flow_warnings(&program),
+            vec![FlowWarning {
+                path: "proxy[silent]/target/inSequence".parse().unwrap(),
+                issue: FlowIssue::NoResponse,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_missing_classes() {
+        let input = r#"
+        <api context="/orders" name="orders">
+            <resource methods="GET">
+                <inSequence>
+                    <class name="org.acme.mediators.Enrich" />
+                    <class name="org.acme.mediators.Audit" />
+                </inSequence>
+            </resource>
+            <handlers>
+                <handler class="org.acme.handlers.Auth" />
+            </handlers>
+        </api>
+        "#;
+
+        let listing = "META-INF/MANIFEST.MF\norg/acme/mediators/\norg/acme/mediators/Enrich.class\norg/acme/mediators/Enrich$Field.class\nmodule-info.class\n";
+        let classes = validate::listed_classes(listing);
+        assert_eq!(
+            classes,
+            vec![
+                "org.acme.mediators.Enrich",
+                "org.acme.mediators.Enrich$Field"
+            ]
+        );
+
+        let program = Parser::new(input.as_bytes()).parse_progarm().unwrap();
+        let available: Vec<&str> = classes.iter().map(String::as_str).collect();
+        assert_eq!(
+            validate::missing_classes(&program, &available),
+            vec![
+                MissingClass {
+                    path: "api[orders]".parse().unwrap(),
+                    class: "org.acme.handlers.Auth".to_string(),
+                },
+                MissingClass {
+                    path: "api[orders]/resource[0]/inSequence/mediator[1]"
+                        .parse()
+                        .unwrap(),
+                    class: "org.acme.mediators.Audit".to_string(),
+                },
+            ]
+        );
+    }
+}